@@ -4,7 +4,10 @@ use ssb_renderer::{
     ssb_parser::{Ssb, SsbRender},
     image::{ColorType, ImageView},
     RenderTrigger,
-    SsbRenderer
+    SsbRenderer,
+    YuvImageView,
+    YuvFormat,
+    ColorMatrix
 };
 use std::{
     convert::TryFrom,
@@ -205,4 +208,109 @@ fn ssb_render_by_id_inner(
         width, height, stride, color_type, planes,
         RenderTrigger::Id(unsafe{ CStr::from_ptr(id) }.to_str()?)
     )
+}
+
+/// Render on planar YUV image by time.
+///
+/// **renderer** can be *null*.
+///
+/// **strides** mustn't be *null* and contains 3 strides (in bytes) for luma & chroma planes.
+///
+/// **format** mustn't be *null* and names subsampling & bits (f.e. "YUV420P10").
+///
+/// **matrix** mustn't be *null* and names color matrix ("BT601", "BT709" or "BT2020").
+///
+/// **full_range** is 0 for limited (TV) range, otherwise full range.
+///
+/// **planes** mustn't be *null* and contains 3 pointers with enough data for given **format** (samples above 8 bits in native endian 16-bit).
+///
+/// **error_message** can be *null*.
+///
+/// Returns 0 on success, 1 on error.
+#[no_mangle]
+pub extern fn ssb_render_yuv_by_time(
+    renderer: *mut c_void,
+    width: c_ushort, height: c_ushort, strides: *const c_uint, format: *const c_char, matrix: *const c_char, full_range: c_int, planes: *const *mut c_uchar,
+    time: c_uint,
+    error_message: *mut c_char, error_message_capacity: c_ushort
+) -> c_int {
+    match ssb_render_yuv_inner(renderer, width, height, strides, format, matrix, full_range, planes, RenderTrigger::Time(time)) {
+        Ok(()) => 0,
+        Err(error) => {
+            error_to_c(error, error_message, error_message_capacity);
+            1
+        }
+    }
+}
+#[allow(clippy::too_many_arguments)]
+fn ssb_render_yuv_inner(
+    renderer: *mut c_void,
+    width: c_ushort, height: c_ushort, strides: *const c_uint, format: *const c_char, matrix: *const c_char, full_range: c_int, planes: *const *mut c_uchar,
+    trigger: RenderTrigger
+) -> Result<(), Box<dyn Error>> {
+    if !renderer.is_null() {
+        unsafe {
+            let format = YuvFormat::by_name(
+                CStr::from_ptr(format).to_str()?,
+                ColorMatrix::by_name( CStr::from_ptr(matrix).to_str()? )?,
+                full_range != 0
+            )?;
+            let strides = from_raw_parts(strides, 3);
+            (*(renderer as *mut SsbRenderer)).render_yuv(
+                YuvImageView::new(
+                    width,
+                    height,
+                    format,
+                    [strides[0], strides[1], strides[2]],
+                    from_raw_parts(planes, 3)
+                        .iter()
+                        .zip(strides)
+                        .enumerate()
+                        .map(|(index, (plane, stride))| from_raw_parts_mut(*plane, format.plane_size(index as u8, width, height).1 as usize * *stride as usize) )
+                        .collect()
+                )?,
+                trigger
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Render on planar YUV image by id.
+///
+/// **renderer** can be *null*.
+///
+/// **strides**, **format**, **matrix**, **full_range** & **planes** like for *ssb_render_yuv_by_time*.
+///
+/// **id** mustn't be *null*.
+///
+/// **error_message** can be *null*.
+///
+/// Returns 0 on success, 1 on error.
+#[no_mangle]
+pub extern fn ssb_render_yuv_by_id(
+    renderer: *mut c_void,
+    width: c_ushort, height: c_ushort, strides: *const c_uint, format: *const c_char, matrix: *const c_char, full_range: c_int, planes: *const *mut c_uchar,
+    id: *const c_char,
+    error_message: *mut c_char, error_message_capacity: c_ushort
+) -> c_int {
+    match ssb_render_yuv_by_id_inner(renderer, width, height, strides, format, matrix, full_range, planes, id) {
+        Ok(()) => 0,
+        Err(error) => {
+            error_to_c(error, error_message, error_message_capacity);
+            1
+        }
+    }
+}
+#[allow(clippy::too_many_arguments)]
+fn ssb_render_yuv_by_id_inner(
+    renderer: *mut c_void,
+    width: c_ushort, height: c_ushort, strides: *const c_uint, format: *const c_char, matrix: *const c_char, full_range: c_int, planes: *const *mut c_uchar,
+    id: *const c_char
+) -> Result<(), Box<dyn Error>> {
+    ssb_render_yuv_inner(
+        renderer,
+        width, height, strides, format, matrix, full_range, planes,
+        RenderTrigger::Id(unsafe{ CStr::from_ptr(id) }.to_str()?)
+    )
 }
\ No newline at end of file
@@ -14,14 +14,16 @@ use ssb_renderer::{
     ssb_parser::{Ssb,SsbRender},
     image::{ColorType,ImageView},
     RenderTrigger,
-    SsbRenderer
+    SsbRenderer,
+    YuvImageView,
+    YuvFormat,
+    ChromaSubsampling,
+    ColorMatrix
 };
 use std::{
     io::{BufRead,BufReader,Cursor},
     fs::File,
     convert::TryFrom,
-    sync::Mutex,
-    cell::RefCell,
     slice::from_raw_parts_mut
 };
 
@@ -86,18 +88,18 @@ fn build_render_filter<R>(clip: Node, reader: R) -> Result<RenderFilter, Error>
     where R: BufRead {
     Ok(RenderFilter{
         source: clip,
-        renderer: Mutex::new(RefCell::new(SsbRenderer::new(
+        renderer: SsbRenderer::new(
             Ssb::default().parse_owned(reader)
             .and_then(SsbRender::try_from)
             .map_err(|err| err_msg(err.to_string()) )?
-        )))
+        )
     })
 }
 
 // Filter class
 struct RenderFilter<'core> {
     source: Node<'core>,
-    renderer: Mutex<RefCell<SsbRenderer>>
+    renderer: SsbRenderer
 }
 impl<'core> Filter<'core> for RenderFilter<'core> {
     // Output video meta information
@@ -131,61 +133,82 @@ impl<'core> Filter<'core> for RenderFilter<'core> {
         let frame = self.source
             .get_frame_filter(context, n)
             .ok_or_else(|| format_err!("Couldn't get the source frame!"))?;
-        // Check RGB(A) format
-        let format = frame.format();
-        if format.color_family() == ColorFamily::RGB && (3..4).contains(&format.plane_count()) && format.sample_type() == SampleType::Integer && format.bits_per_sample() == 8 {
-            // Create lock on renderer
-            if let Ok(renderer_refcell) = self.renderer.lock() {
-                // Make frame copy
-                let mut frame = FrameRefMut::copy_of(core, &frame);
-                // Edit frame by SSB
-                renderer_refcell.borrow_mut().render(
-                    ImageView::new(
-                        frame.width(0) as u16,
-                        frame.height(0) as u16,
-                        frame.stride(0) as u32,
-                        if format.plane_count() == 4 {ColorType::R8G8B8A8} else {ColorType::R8G8B8},
-                        unsafe {
-                            // Serve color planes
-                            let frame_size = frame.height(0) * frame.stride(0);
-                            if format.plane_count() == 4 {
-                                vec![
-                                    from_raw_parts_mut(frame.data_ptr_mut(0), frame_size),
-                                    from_raw_parts_mut(frame.data_ptr_mut(1), frame_size),
-                                    from_raw_parts_mut(frame.data_ptr_mut(2), frame_size),
-                                    from_raw_parts_mut(frame.data_ptr_mut(3), frame_size)
-                                ]
-                            } else {
-                                vec![
-                                    from_raw_parts_mut(frame.data_ptr_mut(0), frame_size),
-                                    from_raw_parts_mut(frame.data_ptr_mut(1), frame_size),
-                                    from_raw_parts_mut(frame.data_ptr_mut(2), frame_size)
-                                ]
-                            }
-                        }
-                    ).map_err(|err| err_msg(err.to_string()) )?,
-                    RenderTrigger::Time(
-                        // Calculate frame time (in milliseconds)
-                        match self.source.info().framerate {
-                            Property::Constant(framerate) => (framerate.denominator as f64 / framerate.numerator as f64 * 1000.0 * n as f64) as u32,
-                            Property::Variable => { // Reserved frame properties: <http://www.vapoursynth.com/doc/apireference.html#reserved-frame-properties>
-                                let frame_props = frame.props();
-                                if let (Ok(duration_numerator), Ok(duration_denominator)) = (frame_props.get_int("_DurationNum"), frame_props.get_int("_DurationDen")) {
-                                    (duration_numerator as f64 / duration_denominator as f64 * 1000.0) as u32
-                                } else {
-                                    bail!("Couldn't get frame time! No constant framerate or variable frame property.")
-                                }
-                            }
-                        }
-                    )
-                ).map_err(|err| err_msg(err.to_string()) )?;
-                // Pass processed frame copy further through the pipeline
-                Ok(frame.into())
-            } else {
-                bail!("Couldn't lock renderer!")
+        // Calculate frame time (in milliseconds)
+        let time = match self.source.info().framerate {
+            Property::Constant(framerate) => (framerate.denominator as f64 / framerate.numerator as f64 * 1000.0 * n as f64) as u32,
+            Property::Variable => { // Reserved frame properties: <http://www.vapoursynth.com/doc/apireference.html#reserved-frame-properties>
+                let frame_props = frame.props();
+                if let (Ok(duration_numerator), Ok(duration_denominator)) = (frame_props.get_int("_DurationNum"), frame_props.get_int("_DurationDen")) {
+                    (duration_numerator as f64 / duration_denominator as f64 * 1000.0) as u32
+                } else {
+                    bail!("Couldn't get frame time! No constant framerate or variable frame property.")
+                }
             }
+        };
+        // Check RGB(A) or planar YUV format
+        let format = frame.format();
+        let rgb = format.color_family() == ColorFamily::RGB && (3..=4).contains(&format.plane_count()) && format.sample_type() == SampleType::Integer && format.bits_per_sample() == 8;
+        let yuv = format.color_family() == ColorFamily::YUV && format.sample_type() == SampleType::Integer && (8..=16).contains(&format.bits_per_sample());
+        if !rgb && !yuv {
+            bail!("Frame format must be RGB24, RGBA32 or planar YUV with 8-16 bits!")
+        }
+        let subsampling = match (format.sub_sampling_w(), format.sub_sampling_h()) {
+            (1, 1) => ChromaSubsampling::Yuv420,
+            (1, 0) => ChromaSubsampling::Yuv422,
+            (0, 0) => ChromaSubsampling::Yuv444,
+            _ => bail!("Frame chroma subsampling must be 4:2:0, 4:2:2 or 4:4:4!")
+        };
+        // Pass unchanged frame without events
+        if !self.renderer.is_active(RenderTrigger::Time(time)) {
+            return Ok(frame);
+        }
+        // Make frame copy
+        let mut frame = FrameRefMut::copy_of(core, &frame);
+        // Serve color planes
+        let planes = unsafe {
+            (0..format.plane_count())
+                .map(|plane| from_raw_parts_mut(frame.data_ptr_mut(plane), frame.height(plane) * frame.stride(plane)))
+                .collect::<Vec<_>>()
+        };
+        // Edit frame by SSB
+        if rgb {
+            self.renderer.render(
+                ImageView::new(
+                    frame.width(0) as u16,
+                    frame.height(0) as u16,
+                    frame.stride(0) as u32,
+                    if format.plane_count() == 4 {ColorType::R8G8B8A8} else {ColorType::R8G8B8},
+                    planes
+                ).map_err(|err| err_msg(err.to_string()) )?,
+                RenderTrigger::Time(time)
+            ).map_err(|err| err_msg(err.to_string()) )?;
         } else {
-            bail!("Frame format must be RGB24 or RGBA32!")
+            // Color space by reserved frame properties, otherwise guessed by resolution
+            let frame_props = frame.props();
+            let matrix = match frame_props.get_int("_Matrix") {
+                Ok(1) => ColorMatrix::BT709,
+                Ok(5) | Ok(6) => ColorMatrix::BT601,
+                Ok(9) | Ok(10) => ColorMatrix::BT2020,
+                _ => if frame.width(0) > 1024 || frame.height(0) > 576 {ColorMatrix::BT709} else {ColorMatrix::BT601}
+            };
+            let full_range = frame_props.get_int("_ColorRange") == Ok(0);
+            self.renderer.render_yuv(
+                YuvImageView::new(
+                    frame.width(0) as u16,
+                    frame.height(0) as u16,
+                    YuvFormat {
+                        subsampling,
+                        bits: format.bits_per_sample(),
+                        matrix,
+                        full_range
+                    },
+                    [frame.stride(0) as u32, frame.stride(1) as u32, frame.stride(2) as u32],
+                    planes
+                ).map_err(|err| err_msg(err.to_string()) )?,
+                RenderTrigger::Time(time)
+            ).map_err(|err| err_msg(err.to_string()) )?;
         }
+        // Pass processed frame copy further through the pipeline
+        Ok(frame.into())
     }
 }
\ No newline at end of file
@@ -47,6 +47,22 @@ mod c_tests {
                 ),
                 0
             );
+            // Try YUV rendering
+            let render_yuv_by_time_fn = lib.get::<unsafe extern fn(*mut c_void, c_ushort, c_ushort, *const c_uint, *const c_char, *const c_char, c_int, *const *mut c_uchar, c_uint, *mut c_char, c_ushort) -> c_int>(b"ssb_render_yuv_by_time\0").expect("Couldn't load symbol 'ssb_render_yuv_by_time' from DLL!");
+            let _render_yuv_by_id_fn = lib.get::<unsafe extern fn(*mut c_void, c_ushort, c_ushort, *const c_uint, *const c_char, *const c_char, c_int, *const *mut c_uchar, *const c_char, *mut c_char, c_ushort) -> c_int>(b"ssb_render_yuv_by_id\0").expect("Couldn't load symbol 'ssb_render_yuv_by_id' from DLL!");
+            assert_eq!(
+                render_yuv_by_time_fn(
+                    renderer,
+                    640, 480, [640*2, 320*2, 320*2].as_ptr(),
+                    "YUV420P10\0".as_ptr() as *const c_char,
+                    "BT709\0".as_ptr() as *const c_char,
+                    0,
+                    vec![vec![0u8;640*480*2], vec![0u8;320*240*2], vec![0u8;320*240*2]].iter_mut().map(|plane| plane.as_mut_ptr() ).collect::<Vec<_>>().as_ptr(),
+                    1000,
+                    null_mut(), 0
+                ),
+                0
+            );
             destroy_renderer_fn(renderer);
             // Error case
             let mut error_message = vec![0 as c_char;128];
@@ -28,7 +28,14 @@ fn main() {
         data: "".to_owned(),
         data_location: (0,0)
     });
-    let mut renderer = SsbRenderer::new(SsbRender::try_from(ssb).expect("Ssb was certainly valid!"));
+    ssb.events.push(Event {
+        trigger: EventTrigger::Id("blur".to_owned()),
+        macro_name: None,
+        note: None,
+        data: "[mode=shape;blur=20,10]m 100 100 l 3740 100 3740 2060 100 2060".to_owned(),
+        data_location: (0,0)
+    });
+    let renderer = SsbRenderer::new(SsbRender::try_from(ssb).expect("Ssb was certainly valid!"));
     // Run test
     bench(&Options::default().time(Duration::from_secs(3)), "Basic rendering.", || {
 
@@ -44,4 +51,12 @@ fn main() {
 
 
     });
+    bench(&Options::default().time(Duration::from_secs(3)), "Blurred 4K rendering.", || {
+        let img = RgbImage::new(3840, 2160);
+        let (width, height, stride, color_type, mut data) = (img.width(), img.height(), img.sample_layout().height_stride, ColorType::RGB24, img.into_raw());
+        renderer.render(
+            ImageView::new(width as u16, height as u16, stride as u32, color_type, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Id("blur")
+        ).expect("Image rendering mustn't fail!");
+    });
 }
\ No newline at end of file
@@ -14,15 +14,22 @@ use puny2d::error::GraphicsError;
 #[derive(Debug)]
 pub struct RenderingError {
     msg: String,
-    src: Box<dyn Error>
+    src: Option<Box<dyn Error>>
 }
 impl RenderingError {
+    /// New error with message only.
+    pub(crate) fn new(msg: &str) -> Self {
+        Self {
+            msg: msg.to_owned(),
+            src: None
+        }
+    }
     /// New error with message and source error.
     pub(crate) fn new_with_source<E>(msg: &str, src: E) -> Self
         where E: Error + 'static {
         Self {
             msg: msg.to_owned(),
-            src: Box::new(src)
+            src: Some(Box::new(src))
         }
     }
 }
@@ -34,7 +41,7 @@ impl Display for RenderingError {
 }
 impl Error for RenderingError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
-        Some(self.src.as_ref())
+        self.src.as_deref()
     }
 }
 impl From<std::io::Error> for RenderingError {
@@ -14,9 +14,12 @@ Renderer component of subtitle format implementation.
 // Project modules
 mod error;
 mod rendering;
+mod state;
+mod raster;
+mod text;
 
 // Exports
-pub use crate::{error::RenderingError, rendering::*};
+pub use crate::{error::RenderingError, rendering::*, raster::yuv::{YuvImageView, YuvFormat, ChromaSubsampling, ColorMatrix}, raster::cache::CacheStats};
 
 // Re-exports (interfaces required by public users).
 pub use puny2d::raster::image;
@@ -0,0 +1,147 @@
+// Imports
+use puny2d::raster::mask::Mask;
+
+
+// Box blur passes to approximate gaussian blur
+const BOX_PASSES: usize = 3;
+
+/// Blurs coverage mask by gaussian standard deviation per dimension (approximated by successive box blurs, so costs don't grow with deviation).
+/// Mask grows by blur extent but stays inside area.
+pub fn blur_mask(mask: Mask, deviation_x: f32, deviation_y: f32, area_width: u16, area_height: u16) -> Mask {
+    let (radii_x, radii_y) = (box_radii(deviation_x), box_radii(deviation_y));
+    let (extent_x, extent_y) = (blur_extent(deviation_x), blur_extent(deviation_y));
+    if extent_x == 0 && extent_y == 0 {
+        return mask;
+    }
+    // Enlarge mask for blurred edges
+    let (x0, y0) = ((mask.x as usize).saturating_sub(extent_x), (mask.y as usize).saturating_sub(extent_y));
+    let (x1, y1) = (
+        (mask.x as usize + mask.width as usize + extent_x).min(area_width as usize),
+        (mask.y as usize + mask.height as usize + extent_y).min(area_height as usize)
+    );
+    let (width, height) = (x1 - x0, y1 - y0);
+    let mut data = vec![0.0; width * height];
+    for mask_y in 0..mask.height as usize {
+        let row = (mask.y as usize - y0 + mask_y) * width + mask.x as usize - x0;
+        data[row..row + mask.width as usize].copy_from_slice(&mask.data[mask_y * mask.width as usize..(mask_y + 1) * mask.width as usize]);
+    }
+    // Separable passes: rows, then columns (row-wise over all columns for cache-friendly access)
+    let mut line = vec![0.0; width];
+    for radius in radii_x.iter().filter(|radius| **radius > 0) {
+        for row in data.chunks_exact_mut(width) {
+            line.copy_from_slice(row);
+            box_blur(&line, row, *radius);
+        }
+    }
+    let mut sums = vec![0.0; width];
+    for radius in radii_y.iter().filter(|radius| **radius > 0) {
+        let source = data.clone();
+        let rows = |index: usize| source.get(index * width..(index + 1) * width);
+        let scale = 1.0 / (radius * 2 + 1) as f32;
+        sums.iter_mut().for_each(|sum| *sum = 0.0);
+        for row in (0..*radius).filter_map(rows) {
+            sums.iter_mut().zip(row).for_each(|(sum, value)| *sum += value);
+        }
+        for (index, destination) in data.chunks_exact_mut(width).enumerate() {
+            if let Some(entering) = rows(index + radius) {
+                sums.iter_mut().zip(entering).for_each(|(sum, value)| *sum += value);
+            }
+            destination.iter_mut().zip(&sums).for_each(|(value, sum)| *value = sum * scale);
+            if let Some(leaving) = index.checked_sub(*radius).and_then(rows) {
+                sums.iter_mut().zip(leaving).for_each(|(sum, value)| *sum -= value);
+            }
+        }
+    }
+    Mask {
+        x: x0 as u16,
+        y: y0 as u16,
+        width: width as u16,
+        height: height as u16,
+        data
+    }
+}
+
+/// Pixels by which blur of gaussian standard deviation spreads coverage.
+pub fn blur_extent(deviation: f32) -> usize {
+    box_radii(deviation).iter().sum()
+}
+
+// Box radii of passes with combined variance of gaussian deviation
+fn box_radii(deviation: f32) -> [usize; BOX_PASSES] {
+    if deviation <= 0.0 {
+        return [0; BOX_PASSES];
+    }
+    let variance = 12.0 * deviation * deviation;
+    let passes = BOX_PASSES as f32;
+    // Odd box sizes around ideal, count of smaller boxes chosen to match variance best
+    let mut lower_size = ((variance / passes + 1.0).sqrt().floor() as usize).max(1);
+    if lower_size & 1 == 0 {
+        lower_size -= 1;
+    }
+    let lower = lower_size as f32;
+    let lower_count = ((variance - passes * lower * lower - 4.0 * passes * lower - 3.0 * passes) / (-4.0 * lower - 4.0)).round().max(0.0) as usize;
+    let mut radii = [0; BOX_PASSES];
+    for (pass, radius) in radii.iter_mut().enumerate() {
+        *radius = if pass < lower_count {lower_size / 2} else {lower_size / 2 + 1};
+    }
+    radii
+}
+
+// Moving average over line (zero outside)
+fn box_blur(source: &[f32], destination: &mut [f32], radius: usize) {
+    let scale = 1.0 / (radius * 2 + 1) as f32;
+    let mut sum = source.iter().take(radius).sum::<f32>();
+    for (index, value) in destination.iter_mut().enumerate() {
+        if let Some(entering) = source.get(index + radius) {
+            sum += entering;
+        }
+        *value = sum * scale;
+        if index >= radius {
+            sum -= source[index - radius];
+        }
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{blur_mask,box_radii,box_blur,Mask};
+
+    #[test]
+    fn blur_radii() {
+        assert_eq!(box_radii(0.0), [0, 0, 0]);
+        assert_eq!(box_radii(1.0), [0, 0, 1]);
+        // Combined variance of boxes near gaussian one
+        let deviation = 5.0;
+        let variance = box_radii(deviation).iter().map(|radius| ((radius * 2 + 1).pow(2) - 1) as f32 / 12.0).sum::<f32>();
+        assert!((variance - deviation * deviation).abs() < 2.0, "{} != {}", variance, deviation * deviation);
+    }
+
+    #[test]
+    fn blur_box() {
+        let mut destination = [0.0; 5];
+        box_blur(&[0.0, 0.0, 3.0, 0.0, 0.0], &mut destination, 1);
+        assert_eq!(destination, [0.0, 1.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn blur_mask_directions() {
+        let mask = Mask {x: 4, y: 4, width: 1, height: 1, data: vec![1.0]};
+        // Horizontal only
+        let blurred = blur_mask(mask.clone(), 2.0, 0.0, 10, 10);
+        assert_eq!((blurred.y, blurred.height), (4, 1));
+        assert!(blurred.x < 4 && blurred.width > 1);
+        assert!((blurred.data.iter().sum::<f32>() - 1.0).abs() < 1e-4, "Blur should keep coverage sum!");
+        // Vertical keeps coverage sum too
+        let blurred = blur_mask(mask.clone(), 0.0, 2.0, 10, 10);
+        assert_eq!((blurred.x, blurred.width), (4, 1));
+        assert!((blurred.data.iter().sum::<f32>() - 1.0).abs() < 1e-4, "Blur should keep coverage sum!");
+        // Clipped by area
+        let blurred = blur_mask(mask.clone(), 2.0, 3.0, 6, 10);
+        assert_eq!(blurred.x as usize + blurred.width as usize, 6);
+        assert!(blurred.data.iter().all(|coverage| *coverage < 1.0));
+        // Nothing to do
+        assert_eq!(blur_mask(mask.clone(), 0.0, -1.0, 10, 10), mask);
+    }
+}
\ No newline at end of file
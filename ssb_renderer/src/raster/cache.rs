@@ -0,0 +1,219 @@
+// Imports
+use std::{
+    fmt,
+    sync::{Arc,Mutex}
+};
+use lru::LruCache;
+use puny2d::{
+    raster::mask::Mask,
+    vector::{
+        point::Point,
+        path::{PathBase,FlatPath,FlatPathSegment}
+    }
+};
+
+
+/// Usage of coverage mask cache.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct CacheStats {
+    /// Masks reused.
+    pub hits: u64,
+    /// Masks rasterized.
+    pub misses: u64,
+    /// Masks cached.
+    pub entries: usize,
+    /// Memory of cached masks (in bytes).
+    pub bytes: usize
+}
+
+/// Identity of a coverage mask: geometry relative to its frame (thus glyph or shape outline after font, size, transform & border were applied, with subpixel origin),
+/// blur and frame size. Masks cover the whole frame, independent of image tiles. Translated geometries share a key if relative to their integer pixel origin.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct MaskKey {
+    path: Vec<u32>,
+    blur: (u32, u32),
+    image: (u16, u16)
+}
+impl MaskKey {
+    /// Key of path filled in frame (image or path extent), blurred by deviations.
+    pub fn new(path: &FlatPath, blur: (f32, f32), width: u16, height: u16) -> Self {
+        let point_bits = |point: &Point| [point.x.to_bits(), point.y.to_bits()];
+        Self {
+            path: path.segments().iter()
+                .flat_map(|segment| match segment {
+                    FlatPathSegment::MoveTo(point) => {let [x, y] = point_bits(point); vec![0, x, y]}
+                    FlatPathSegment::LineTo(point) => {let [x, y] = point_bits(point); vec![1, x, y]}
+                    FlatPathSegment::Close => vec![2]
+                })
+                .collect(),
+            blur: (blur.0.to_bits(), blur.1.to_bits()),
+            image: (width, height)
+        }
+    }
+    // Memory of key
+    fn bytes(&self) -> usize {
+        std::mem::size_of::<Self>() + self.path.len() * std::mem::size_of::<u32>()
+    }
+}
+
+// Cached masks (none for invisible geometries) with usage
+struct MaskCacheState {
+    masks: LruCache<MaskKey, Option<Arc<Mask>>>,
+    bytes: usize,
+    hits: u64,
+    misses: u64
+}
+
+/// Least recently used coverage masks, bounded by count & memory. Shareable by threads.
+pub struct MaskCache {
+    max_entries: usize,
+    max_bytes: usize,
+    state: Mutex<MaskCacheState>
+}
+impl MaskCache {
+    /// Empty cache with limits of mask count & memory (in bytes).
+    pub fn new(max_entries: usize, max_bytes: usize) -> Self {
+        Self {
+            max_entries,
+            max_bytes,
+            state: Mutex::new(MaskCacheState {
+                masks: LruCache::unbounded(),
+                bytes: 0,
+                hits: 0,
+                misses: 0
+            })
+        }
+    }
+    /// Changes limits, dropping least recently used masks beyond.
+    pub fn set_limits(&mut self, max_entries: usize, max_bytes: usize) {
+        self.max_entries = max_entries;
+        self.max_bytes = max_bytes;
+        let state = self.state.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner());
+        evict(state, max_entries, max_bytes);
+    }
+    /// Cached mask by key or created one (cached if it fits into limits).
+    /// Creation happens unlocked, so threads may rasterize the same mask concurrently.
+    pub fn get_or_insert_with<F>(&self, key: MaskKey, create: F) -> Option<Arc<Mask>>
+        where F: FnOnce() -> Option<Mask> {
+        {
+            let mut state = self.lock();
+            if let Some(mask) = state.masks.get(&key).cloned() {
+                state.hits += 1;
+                return mask;
+            }
+        }
+        let mask = create().map(Arc::new);
+        let bytes = entry_bytes(&key, &mask);
+        let mut state = self.lock();
+        state.misses += 1;
+        if self.max_entries > 0 && bytes <= self.max_bytes {
+            if let Some(replaced) = state.masks.put(key.clone(), mask.clone()) {
+                state.bytes -= entry_bytes(&key, &replaced);
+            }
+            state.bytes += bytes;
+            evict(&mut state, self.max_entries, self.max_bytes);
+        }
+        mask
+    }
+    /// Current usage.
+    pub fn stats(&self) -> CacheStats {
+        let state = self.lock();
+        CacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            entries: state.masks.len(),
+            bytes: state.bytes
+        }
+    }
+    // Locked state (a panicking renderer thread leaves a consistent cache)
+    fn lock(&self) -> std::sync::MutexGuard<'_, MaskCacheState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+impl Clone for MaskCache {
+    // Clones start empty with same limits
+    fn clone(&self) -> Self {
+        Self::new(self.max_entries, self.max_bytes)
+    }
+}
+impl PartialEq for MaskCache {
+    // Cached content doesn't change rendering
+    fn eq(&self, other: &Self) -> bool {
+        self.max_entries == other.max_entries && self.max_bytes == other.max_bytes
+    }
+}
+impl fmt::Debug for MaskCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaskCache")
+            .field("max_entries", &self.max_entries)
+            .field("max_bytes", &self.max_bytes)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+// Memory of cache entry
+fn entry_bytes(key: &MaskKey, mask: &Option<Arc<Mask>>) -> usize {
+    key.bytes() + mask.as_ref().map_or(0, |mask| mask.data.len() * std::mem::size_of::<f32>())
+}
+
+// Drops least recently used masks until limits are kept
+fn evict(state: &mut MaskCacheState, max_entries: usize, max_bytes: usize) {
+    while state.masks.len() > max_entries || state.bytes > max_bytes {
+        match state.masks.pop_lru() {
+            Some((key, mask)) => state.bytes -= entry_bytes(&key, &mask),
+            None => break
+        }
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{MaskCache,MaskKey,CacheStats,Mask,FlatPath,Point,PathBase};
+
+    fn key(x: f32) -> MaskKey {
+        let mut path = FlatPath::default();
+        path.move_to(Point {x, y: 0.0});
+        path.line_to(Point {x: 4.0, y: 4.0});
+        path.close();
+        MaskKey::new(&path, (0.0, 0.0), 8, 8)
+    }
+
+    fn mask() -> Option<Mask> {
+        Some(Mask {x: 0, y: 0, width: 2, height: 2, data: vec![1.0; 4]})
+    }
+
+    #[test]
+    fn cache_hits_misses() {
+        let cache = MaskCache::new(2, 1024);
+        assert_eq!(cache.get_or_insert_with(key(0.0), mask).map(|mask| mask.width), Some(2));
+        assert_eq!(cache.get_or_insert_with(key(0.0), || panic!("Mask should be cached!")).map(|mask| mask.width), Some(2));
+        assert!(cache.get_or_insert_with(key(1.0), || None).is_none());
+        assert!(cache.get_or_insert_with(key(1.0), || panic!("Invisible mask should be cached!")).is_none());
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 2, 2));
+        assert!(stats.bytes > 4 * 4);
+    }
+
+    #[test]
+    fn cache_limits() {
+        let mut cache = MaskCache::new(2, 1024);
+        cache.get_or_insert_with(key(0.0), mask);
+        cache.get_or_insert_with(key(1.0), mask);
+        cache.get_or_insert_with(key(0.0), mask);
+        // Least recently used one dropped
+        cache.get_or_insert_with(key(2.0), mask);
+        assert_eq!(cache.stats().entries, 2);
+        cache.get_or_insert_with(key(0.0), || panic!("Mask should be cached!"));
+        assert_eq!(cache.stats().misses, 3);
+        // Memory bound
+        cache.set_limits(2, key(0.0).bytes() + 4 * 4);
+        assert_eq!(cache.stats().entries, 1);
+        cache.set_limits(0, 1024);
+        assert_eq!(cache.stats(), CacheStats {hits: 2, misses: 3, entries: 0, bytes: 0});
+        cache.get_or_insert_with(key(0.0), mask);
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,149 @@
+// Imports
+use ssb_parser::objects::event_objects::Blend;
+use puny2d::raster::{
+    image::{ColorType,ImageView},
+    mask::Mask
+};
+use super::paint::Paint;
+
+
+/// Location of color channels in image data.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ChannelLayout {
+    /// Distance between pixels in one plane.
+    pub sample_size: usize,
+    /// Plane index & pixel offset of red, green & blue channels.
+    pub colors: [(u8, usize);3],
+    /// Plane index & pixel offset of alpha channel (if existing).
+    pub alpha: Option<(u8, usize)>
+}
+impl From<ColorType> for ChannelLayout {
+    fn from(color_type: ColorType) -> Self {
+        match color_type {
+            ColorType::RGB24 => Self {sample_size: 3, colors: [(0, 0), (0, 1), (0, 2)], alpha: None},
+            ColorType::BGR24 => Self {sample_size: 3, colors: [(0, 2), (0, 1), (0, 0)], alpha: None},
+            ColorType::R8G8B8 => Self {sample_size: 1, colors: [(0, 0), (1, 0), (2, 0)], alpha: None},
+            ColorType::RGBA32 => Self {sample_size: 4, colors: [(0, 0), (0, 1), (0, 2)], alpha: Some((0, 3))},
+            ColorType::ABGR32 => Self {sample_size: 4, colors: [(0, 3), (0, 2), (0, 1)], alpha: Some((0, 0))},
+            ColorType::R8G8B8A8 => Self {sample_size: 1, colors: [(0, 0), (1, 0), (2, 0)], alpha: Some((3, 0))}
+        }
+    }
+}
+
+/// Draws paint through coverage mask on image, colors blended with image ones by optional mode.
+/// Images with alpha channel are composed by 'over' operator on straight alpha, blending affects only opaque image parts.
+pub fn fill_mask(img: &mut ImageView, mask: &Mask, paint: &Paint, blend: Option<&Blend>) {
+    let (layout, stride) = (ChannelLayout::from(img.color_type()), img.stride() as usize);
+    let (width, height) = (
+        (mask.width as usize).min((img.width() as usize).saturating_sub(mask.x as usize)),
+        (mask.height as usize).min((img.height() as usize).saturating_sub(mask.y as usize))
+    );
+    let mono_paint = if paint.is_mono() {Some(paint.at(0, 0))} else {None};
+    for mask_y in 0..height {
+        for mask_x in 0..width {
+            // Effective opacity of pixel
+            let coverage = mask.data[mask_y * mask.width as usize + mask_x].min(1.0);
+            if coverage <= 0.0 {
+                continue;
+            }
+            let (color, alpha) = mono_paint.unwrap_or_else(|| paint.at(mask.x + mask_x as u16, mask.y + mask_y as u16));
+            let opacity = coverage * alpha;
+            if opacity <= 0.0 {
+                continue;
+            }
+            let pixel_offset = (mask.y as usize + mask_y) * stride + (mask.x as usize + mask_x) * layout.sample_size;
+            // Weights of source & destination
+            let (destination_alpha, source_weight, destination_weight) = if let Some((alpha_plane, alpha_offset)) = layout.alpha {
+                let destination_sample = &mut img.plane_mut(alpha_plane).expect("Alpha plane should exist by color type!")[pixel_offset + alpha_offset];
+                let destination_alpha = *destination_sample as f32 / u8::MAX as f32;
+                let result_alpha = opacity + destination_alpha * (1.0 - opacity);
+                *destination_sample = (result_alpha * u8::MAX as f32).round() as u8;
+                (destination_alpha, opacity / result_alpha, destination_alpha * (1.0 - opacity) / result_alpha)
+            } else {
+                (1.0, opacity, 1.0 - opacity)
+            };
+            // Mix colors
+            for (channel, (plane, offset)) in layout.colors.iter().enumerate() {
+                let destination_sample = &mut img.plane_mut(*plane).expect("Color plane should exist by color type!")[pixel_offset + offset];
+                let destination_color = *destination_sample as f32;
+                let source_color = match blend {
+                    Some(blend) => color[channel] * (1.0 - destination_alpha) + blend_channel(blend, destination_color, color[channel]) * destination_alpha,
+                    None => color[channel]
+                };
+                *destination_sample = (source_color * source_weight + destination_color * destination_weight).round().clamp(0.0, u8::MAX as f32) as u8;
+            }
+        }
+    }
+}
+
+// Blend of color channels (0-255)
+fn blend_channel(blend: &Blend, destination: f32, source: f32) -> f32 {
+    const MAX: f32 = u8::MAX as f32;
+    match blend {
+        Blend::Add => (destination + source).min(MAX),
+        Blend::Subtract => (destination - source).max(0.0),
+        Blend::Multiply => destination * source / MAX,
+        Blend::Invert => MAX - destination,
+        Blend::Difference => (destination - source).abs(),
+        Blend::Screen => destination + source - destination * source / MAX
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{fill_mask,blend_channel,ColorType,ImageView,Mask,Paint,Blend};
+    use ssb_parser::objects::event_objects::{Color,Alpha};
+    use puny2d::vector::point::Point;
+
+    fn test_paint() -> Paint {
+        Paint::new(&Color::Mono([255, 128, 0]), &Alpha::Mono(255), (Point::default(), Point::default()))
+    }
+
+    fn test_mask() -> Mask {
+        Mask {
+            x: 1,
+            y: 0,
+            width: 2,
+            height: 1,
+            data: vec![1.0, 0.5]
+        }
+    }
+
+    #[test]
+    fn fill_rgb() {
+        let mut data = vec![0u8; 3 * 3];
+        fill_mask(&mut ImageView::new(3, 1, 9, ColorType::BGR24, vec![&mut data]).unwrap(), &test_mask(), &test_paint(), None);
+        assert_eq!(data, vec![0, 0, 0, 0, 128, 255, 0, 64, 128]);
+    }
+
+    #[test]
+    fn fill_rgba_planar() {
+        let (mut r, mut g, mut b, mut a) = (vec![0u8; 3], vec![0u8; 3], vec![0u8; 3], vec![0u8; 3]);
+        fill_mask(&mut ImageView::new(3, 1, 3, ColorType::R8G8B8A8, vec![&mut r, &mut g, &mut b, &mut a]).unwrap(), &test_mask(), &test_paint(), None);
+        assert_eq!((r, g, b, a), (vec![0, 255, 255], vec![0, 128, 128], vec![0, 0, 0], vec![0, 255, 128]));
+    }
+
+    #[test]
+    fn blend_modes() {
+        assert_eq!(blend_channel(&Blend::Add, 200.0, 100.0), 255.0);
+        assert_eq!(blend_channel(&Blend::Subtract, 100.0, 200.0), 0.0);
+        assert_eq!(blend_channel(&Blend::Multiply, 255.0, 51.0), 51.0);
+        assert_eq!(blend_channel(&Blend::Invert, 55.0, 0.0), 200.0);
+        assert_eq!(blend_channel(&Blend::Difference, 50.0, 200.0), 150.0);
+        assert_eq!(blend_channel(&Blend::Screen, 0.0, 51.0), 51.0);
+    }
+
+    #[test]
+    fn fill_blended() {
+        // Opaque image gets blended
+        let mut data = vec![100u8; 3 * 3];
+        fill_mask(&mut ImageView::new(3, 1, 9, ColorType::RGB24, vec![&mut data]).unwrap(), &test_mask(), &test_paint(), Some(&Blend::Add));
+        assert_eq!(data, vec![100, 100, 100, 255, 228, 100, 178, 164, 100]);
+        // Transparent image gets painted over
+        let mut data = vec![100, 100, 100, 0, 100, 100, 100, 0, 100, 100, 100, 0];
+        fill_mask(&mut ImageView::new(3, 1, 12, ColorType::RGBA32, vec![&mut data]).unwrap(), &test_mask(), &test_paint(), Some(&Blend::Multiply));
+        assert_eq!(data, vec![100, 100, 100, 0, 255, 128, 0, 255, 255, 128, 0, 128]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,24 @@
+// Geometries to paths conversion.
+pub mod path;
+// Points geometries to dots.
+pub mod points;
+// Path outlines by pen.
+pub mod stroke;
+// Path rasterization to coverage masks.
+pub mod scanline;
+// Softening of coverage masks.
+pub mod blur;
+// Images for geometry filling.
+pub mod texture;
+// Color sources of geometries.
+pub mod paint;
+// Clipping by drawn geometries.
+pub mod stencil;
+// Reuse of coverage masks between renderings.
+pub mod cache;
+// Image division for parallel drawing.
+pub mod tile;
+// Composition of coverage masks on images.
+pub mod composite;
+// Subtitle composition on planar YUV images.
+pub mod yuv;
\ No newline at end of file
@@ -0,0 +1,168 @@
+// Imports
+use std::sync::Arc;
+use ssb_parser::objects::event_objects::{Color,Alpha,Rgb,TextureWrapping};
+use puny2d::vector::{
+    types::Coordinate,
+    point::Point
+};
+use super::texture::Texture;
+
+
+/// Color & alpha source over area (bounding box of geometry in image).
+/// Gradients run from left to right (linear, with stop in center) or between corners clockwise from top-left (with stop in center).
+/// A texture replaces colors and multiplies its alpha with the alpha gradient.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Paint {
+    colors: Vec<Rgb>,
+    alphas: Vec<u8>,
+    area: (Point, Point),
+    texture: Option<(Arc<Texture>, (Point, Point), TextureWrapping)>
+}
+impl Paint {
+    /// Paint by color & alpha (0 = transparent, 255 = opaque) variants over area.
+    pub fn new(color: &Color, alpha: &Alpha, area: (Point, Point)) -> Self {
+        Self {
+            colors: match color {
+                Color::Mono(color) => vec![*color],
+                Color::Linear(colors) => colors.to_vec(),
+                Color::LinearWithStop(colors) => colors.to_vec(),
+                Color::Corners(colors) => colors.to_vec(),
+                Color::CornersWithStop(colors) => colors.to_vec()
+            },
+            alphas: match alpha {
+                Alpha::Mono(alpha) => vec![*alpha],
+                Alpha::Linear(alphas) => alphas.to_vec(),
+                Alpha::LinearWithStop(alphas) => alphas.to_vec(),
+                Alpha::Corners(alphas) => alphas.to_vec(),
+                Alpha::CornersWithStop(alphas) => alphas.to_vec()
+            },
+            area,
+            texture: None
+        }
+    }
+    /// Adds texture, stretched between start & end point (relative to area, 0 = left/top, 1 = right/bottom) and wrapped outside.
+    pub fn with_texture(mut self, texture: Arc<Texture>, start: Point, end: Point, wrap: TextureWrapping) -> Self {
+        let (min, max) = self.area;
+        let size = max - min;
+        self.texture = Some((
+            texture,
+            (
+                Point {x: min.x + start.x * size.x, y: min.y + start.y * size.y},
+                Point {x: min.x + end.x * size.x, y: min.y + end.y * size.y}
+            ),
+            wrap
+        ));
+        self
+    }
+    /// Paint moved by offset (f.e. onto image parts).
+    pub fn translated(&self, offset: Point) -> Self {
+        Self {
+            area: (self.area.0 + offset, self.area.1 + offset),
+            texture: self.texture.as_ref().map(|(texture, (start, end), wrap)| (texture.clone(), (*start + offset, *end + offset), wrap.clone())),
+            ..self.clone()
+        }
+    }
+    /// Paint without variation over area?
+    pub fn is_mono(&self) -> bool {
+        self.colors.len() == 1 && self.alphas.len() == 1 && self.texture.is_none()
+    }
+    /// Color (channels 0-255) & opacity (0-1) at pixel center.
+    pub fn at(&self, x: u16, y: u16) -> ([f32;3], f32) {
+        let (min, max) = self.area;
+        let relative = |value: Coordinate, min: Coordinate, max: Coordinate| if max > min {((value - min) / (max - min)).clamp(0.0, 1.0)} else {0.5};
+        let (u, v) = (relative(x as Coordinate + 0.5, min.x, max.x), relative(y as Coordinate + 0.5, min.y, max.y));
+        let mut color = [0.0; 3];
+        for (stop_color, weight) in self.colors.iter().zip(&stop_weights(self.colors.len(), u, v)) {
+            for (channel, stop_channel) in color.iter_mut().zip(stop_color) {
+                *channel += *stop_channel as f32 * weight;
+            }
+        }
+        let alpha = self.alphas.iter().zip(&stop_weights(self.alphas.len(), u, v)).map(|(stop_alpha, weight)| *stop_alpha as f32 * weight).sum::<f32>() / u8::MAX as f32;
+        match &self.texture {
+            Some((texture, (start, end), wrap)) => {
+                if end.x == start.x || end.y == start.y {
+                    return ([0.0; 3], 0.0);
+                }
+                let (texture_color, texture_alpha) = texture.sample(
+                    (x as Coordinate + 0.5 - start.x) / (end.x - start.x) * texture.width() as Coordinate,
+                    (y as Coordinate + 0.5 - start.y) / (end.y - start.y) * texture.height() as Coordinate,
+                    wrap
+                );
+                (texture_color, texture_alpha * alpha)
+            }
+            None => (color, alpha)
+        }
+    }
+}
+
+// Weights of gradient stops at relative position in area
+fn stop_weights(stops: usize, u: f32, v: f32) -> [f32;5] {
+    match stops {
+        2 => [1.0 - u, u, 0.0, 0.0, 0.0],
+        3 if u < 0.5 => [1.0 - u * 2.0, u * 2.0, 0.0, 0.0, 0.0],
+        3 => [0.0, 2.0 - u * 2.0, u * 2.0 - 1.0, 0.0, 0.0],
+        4 | 5 => {
+            let center = if stops == 5 {(1.0 - (u * 2.0 - 1.0).abs()) * (1.0 - (v * 2.0 - 1.0).abs())} else {0.0};
+            let corners = 1.0 - center;
+            [(1.0 - u) * (1.0 - v) * corners, u * (1.0 - v) * corners, u * v * corners, (1.0 - u) * v * corners, center]
+        }
+        _ => [1.0, 0.0, 0.0, 0.0, 0.0]
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{Paint,Color,Alpha,Point,Texture,TextureWrapping};
+
+    fn assert_paint(paint: &Paint, x: u16, y: u16, expected: ([f32;3], f32)) {
+        let (color, alpha) = paint.at(x, y);
+        assert!(
+            color.iter().zip(&expected.0).all(|(channel, expected_channel)| (channel - expected_channel).abs() < 1e-3) && (alpha - expected.1).abs() < 1e-6,
+            "Paint at {},{}: {:?} != {:?}", x, y, (color, alpha), expected
+        );
+    }
+
+    #[test]
+    fn paint_linear() {
+        let paint = Paint::new(&Color::Linear([[0, 0, 0], [200, 100, 0]]), &Alpha::LinearWithStop([0, 255, 0]), (Point {x: 0.0, y: 0.0}, Point {x: 10.0, y: 10.0}));
+        assert!(!paint.is_mono());
+        assert_paint(&paint, 0, 5, ([10.0, 5.0, 0.0], 0.1));
+        assert_paint(&paint, 9, 0, ([190.0, 95.0, 0.0], 0.1));
+        assert_paint(&paint, 4, 9, ([90.0, 45.0, 0.0], 0.9));
+    }
+
+    #[test]
+    fn paint_corners() {
+        let paint = Paint::new(&Color::Corners([[255, 0, 0], [0, 255, 0], [0, 0, 255], [0, 0, 0]]), &Alpha::CornersWithStop([0, 0, 0, 0, 255]), (Point {x: 0.5, y: 0.5}, Point {x: 2.5, y: 2.5}));
+        assert_paint(&paint, 0, 0, ([255.0, 0.0, 0.0], 0.0));
+        assert_paint(&paint, 2, 0, ([0.0, 255.0, 0.0], 0.0));
+        assert_paint(&paint, 1, 1, ([63.75, 63.75, 63.75], 1.0));
+        assert_paint(&paint, 2, 2, ([0.0, 0.0, 255.0], 0.0));
+        assert!(Paint::new(&Color::Mono([1, 2, 3]), &Alpha::Mono(4), (Point {x: 0.0, y: 0.0}, Point {x: 0.0, y: 0.0})).is_mono());
+    }
+
+    #[test]
+    fn paint_texture() {
+        let texture = std::sync::Arc::new(Texture::decode(&{
+            let mut png_data = vec![];
+            let mut encoder = png::Encoder::new(&mut png_data, 2, 1);
+            encoder.set_color(png::ColorType::RGB);
+            encoder.write_header().and_then(|mut writer| writer.write_image_data(&[255, 0, 0, 0, 0, 255]))
+                .expect("PNG encoding shouldn't fail!");
+            png_data
+        }).expect("PNG should be decodable!"));
+        // One pixel per texel, texture repeated over second half
+        let area = (Point {x: 0.0, y: 0.0}, Point {x: 4.0, y: 4.0});
+        let paint = Paint::new(&Color::Mono([0, 255, 0]), &Alpha::Linear([255, 0]), area)
+            .with_texture(texture.clone(), Point {x: 0.0, y: 0.0}, Point {x: 0.5, y: 1.0}, TextureWrapping::Repeat);
+        assert!(!paint.is_mono());
+        assert_paint(&paint, 0, 1, ([255.0, 0.0, 0.0], 0.875));
+        assert_paint(&paint, 1, 1, ([0.0, 0.0, 255.0], 0.625));
+        assert_paint(&paint, 2, 1, ([255.0, 0.0, 0.0], 0.375));
+        let paint = Paint::new(&Color::Mono([0, 255, 0]), &Alpha::Mono(255), area)
+            .with_texture(texture, Point {x: 0.0, y: 0.0}, Point {x: 0.5, y: 1.0}, TextureWrapping::Pad);
+        assert_paint(&paint, 3, 1, ([0.0; 3], 0.0));
+    }
+}
\ No newline at end of file
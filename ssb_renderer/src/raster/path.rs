@@ -0,0 +1,76 @@
+// Imports
+use ssb_parser::objects::event_objects::{ShapeSegment,Point2D};
+use puny2d::vector::{
+    point::Point,
+    path::{PathBase,Path,FlatPath,PathSegment,FlatPathSegment}
+};
+
+
+/// Builds path from shape segments.
+pub fn shape_to_path(segments: &[ShapeSegment]) -> Path {
+    let to_point = |point: &Point2D| Point {x: point.x, y: point.y};
+    let mut path = Path::default();
+    for segment in segments {
+        match segment {
+            ShapeSegment::MoveTo(point) => {path.move_to(to_point(point));}
+            ShapeSegment::LineTo(point) => {path.line_to(to_point(point));}
+            ShapeSegment::CurveTo(control_point1, control_point2, end_point) => {path.curve_to(to_point(control_point1), to_point(control_point2), to_point(end_point));}
+            ShapeSegment::ArcBy(center_point, angle) => {path.arc_by(to_point(center_point), *angle as f64);}
+            ShapeSegment::Close => {path.close();}
+        }
+    }
+    path
+}
+
+/// Builds new path with all points (including curve control points) modified by given function.
+pub fn map_path<F>(path: &Path, point_map: F) -> Path
+    where F: Fn(Point) -> Point {
+    Path::new(
+        path.segments().iter()
+        .map(|segment| match segment {
+            PathSegment::Flat(FlatPathSegment::MoveTo(point)) => PathSegment::Flat(FlatPathSegment::MoveTo(point_map(*point))),
+            PathSegment::Flat(FlatPathSegment::LineTo(point)) => PathSegment::Flat(FlatPathSegment::LineTo(point_map(*point))),
+            PathSegment::Flat(FlatPathSegment::Close) => PathSegment::Flat(FlatPathSegment::Close),
+            PathSegment::CurveTo(control_point1, control_point2, end_point) => PathSegment::CurveTo(point_map(*control_point1), point_map(*control_point2), point_map(*end_point))
+        })
+        .collect()
+    )
+}
+
+/// Builds new flat path with all points modified by given function (for non-affine mappings like perspective).
+pub fn map_flat_path<F>(path: &FlatPath, point_map: F) -> FlatPath
+    where F: Fn(Point) -> Point {
+    FlatPath::new(
+        path.segments().iter()
+        .map(|segment| match segment {
+            FlatPathSegment::MoveTo(point) => FlatPathSegment::MoveTo(point_map(*point)),
+            FlatPathSegment::LineTo(point) => FlatPathSegment::LineTo(point_map(*point)),
+            FlatPathSegment::Close => FlatPathSegment::Close
+        })
+        .collect()
+    )
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{shape_to_path,map_path,ShapeSegment,Point2D,Point,PathBase,PathSegment,FlatPathSegment};
+
+    #[test]
+    fn shape_path() {
+        let path = shape_to_path(&[
+            ShapeSegment::MoveTo(Point2D {x: 1.0, y: 2.0}),
+            ShapeSegment::LineTo(Point2D {x: 3.0, y: 4.0}),
+            ShapeSegment::Close
+        ]);
+        assert_eq!(
+            map_path(&path, |point| Point {x: point.x * 2.0, y: point.y}).segments(),
+            &[
+                PathSegment::Flat(FlatPathSegment::MoveTo(Point {x: 2.0, y: 2.0})),
+                PathSegment::Flat(FlatPathSegment::LineTo(Point {x: 6.0, y: 4.0})),
+                PathSegment::Flat(FlatPathSegment::Close)
+            ]
+        );
+    }
+}
\ No newline at end of file
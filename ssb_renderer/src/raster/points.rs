@@ -0,0 +1,69 @@
+// Imports
+use ssb_parser::objects::event_objects::{Point2D,Cap,Coordinate};
+use puny2d::vector::{
+    point::Point,
+    path::{PathBase,Path}
+};
+
+
+// Smallest dot radius, so points never disappear
+const MIN_RADIUS: Coordinate = 0.5;
+
+/// Builds path of dots around points with given radius per dimension.
+/// Dots are shaped by cap: round = ellipse, square = rectangle, butt = nothing (like zero-length lines).
+pub fn points_to_path(points: &[Point2D], radius_x: Coordinate, radius_y: Coordinate, cap: &Cap) -> Path {
+    let (radius_x, radius_y) = (radius_x.max(MIN_RADIUS), radius_y.max(MIN_RADIUS));
+    let mut path = Path::default();
+    for point in points {
+        match cap {
+            Cap::Round => {
+                // Quarter curves of unit circle, stretched to ellipse
+                const CONTROL_DISTANCE: Coordinate = 0.552_284_8;
+                let ellipse_point = |x: Coordinate, y: Coordinate| Point {x: point.x + x * radius_x, y: point.y + y * radius_y};
+                path.move_to(ellipse_point(1.0, 0.0))
+                    .curve_to(ellipse_point(1.0, CONTROL_DISTANCE), ellipse_point(CONTROL_DISTANCE, 1.0), ellipse_point(0.0, 1.0))
+                    .curve_to(ellipse_point(-CONTROL_DISTANCE, 1.0), ellipse_point(-1.0, CONTROL_DISTANCE), ellipse_point(-1.0, 0.0))
+                    .curve_to(ellipse_point(-1.0, -CONTROL_DISTANCE), ellipse_point(-CONTROL_DISTANCE, -1.0), ellipse_point(0.0, -1.0))
+                    .curve_to(ellipse_point(CONTROL_DISTANCE, -1.0), ellipse_point(1.0, -CONTROL_DISTANCE), ellipse_point(1.0, 0.0))
+                    .close();
+            }
+            Cap::Square => {
+                path.move_to(Point {x: point.x - radius_x, y: point.y - radius_y})
+                    .line_to(Point {x: point.x + radius_x, y: point.y - radius_y})
+                    .line_to(Point {x: point.x + radius_x, y: point.y + radius_y})
+                    .line_to(Point {x: point.x - radius_x, y: point.y + radius_y})
+                    .close();
+            }
+            Cap::Butt => {}
+        }
+    }
+    path
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{points_to_path,Point2D,Cap,Point,PathBase};
+    use puny2d::vector::path::{FlatPath,PathSegment,FlatPathSegment};
+
+    #[test]
+    fn points_caps() {
+        let points = [Point2D {x: 10.0, y: 5.0}, Point2D {x: 0.0, y: 0.0}];
+        assert!(points_to_path(&points, 2.0, 2.0, &Cap::Butt).segments().is_empty());
+        assert_eq!(
+            &points_to_path(&points, 2.0, 0.0, &Cap::Square).segments()[..5],
+            &[
+                PathSegment::Flat(FlatPathSegment::MoveTo(Point {x: 8.0, y: 4.5})),
+                PathSegment::Flat(FlatPathSegment::LineTo(Point {x: 12.0, y: 4.5})),
+                PathSegment::Flat(FlatPathSegment::LineTo(Point {x: 12.0, y: 5.5})),
+                PathSegment::Flat(FlatPathSegment::LineTo(Point {x: 8.0, y: 5.5})),
+                PathSegment::Flat(FlatPathSegment::Close)
+            ]
+        );
+        assert_eq!(
+            FlatPath::from(points_to_path(&points[..1], 3.0, 1.0, &Cap::Round)).bounding(),
+            Some((Point {x: 7.0, y: 4.0}, Point {x: 13.0, y: 6.0}))
+        );
+    }
+}
\ No newline at end of file
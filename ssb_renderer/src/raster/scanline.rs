@@ -0,0 +1,212 @@
+// Imports
+use puny2d::{
+    raster::mask::Mask,
+    vector::{
+        types::Coordinate,
+        point::Point,
+        path::{PathBase,FlatPath,FlatPathSegment}
+    }
+};
+
+
+// Vertical samples per pixel row (horizontal coverage is exact)
+const SUBSCANLINES: usize = 16;
+const SUBSCANLINE_WEIGHT: f32 = 1.0 / SUBSCANLINES as f32;
+
+// Line of path, oriented top-down with original direction as winding
+struct Edge {
+    top: Point,
+    bottom: Point,
+    winding: i32
+}
+
+/// Fills path into coverage mask by non-zero winding rule. Open sub-paths get closed implicitly.
+/// Coverage is clipped to area and anti-aliased by exact horizontal spans on vertical sub-scanlines.
+pub fn fill_path(path: &FlatPath, area_width: u16, area_height: u16) -> Option<Mask> {
+    // Collect edges with boundings, sorted top-down for scanning
+    let mut edges = path_edges(path);
+    let (mut min_point, mut max_point) = (edges.first().map(|edge| edge.top)?, edges.first().map(|edge| edge.bottom)?);
+    for edge in &edges {
+        min_point = min_point.min(edge.top).min(edge.bottom);
+        max_point = max_point.max(edge.top).max(edge.bottom);
+    }
+    // Mask dimensions in area
+    let (mask_x, mask_y, mask_right, mask_bottom) = (
+        min_point.x.floor().max(0.0).min(area_width as Coordinate) as u16,
+        min_point.y.floor().max(0.0).min(area_height as Coordinate) as u16,
+        max_point.x.ceil().max(0.0).min(area_width as Coordinate) as u16,
+        max_point.y.ceil().max(0.0).min(area_height as Coordinate) as u16
+    );
+    if mask_right <= mask_x || mask_bottom <= mask_y {
+        return None;
+    }
+    let mut mask = Mask {
+        x: mask_x,
+        y: mask_y,
+        width: mask_right - mask_x,
+        height: mask_bottom - mask_y,
+        data: vec![0.0; (mask_right - mask_x) as usize * (mask_bottom - mask_y) as usize]
+    };
+    edges.sort_by(|edge1, edge2| edge1.top.y.partial_cmp(&edge2.top.y).unwrap_or(std::cmp::Ordering::Equal));
+    // Rasterize row by row, with edges crossing current sub-scanline active
+    let mask_width = mask.width as usize;
+    let mut full_coverage = vec![0.0f32; mask_width + 1];
+    let mut crossings: Vec<(Coordinate, i32)> = Vec::with_capacity(8);
+    let (mut active_edges, mut next_edge): (Vec<&Edge>, usize) = (Vec::with_capacity(8), 0);
+    for (row_index, row) in mask.data.chunks_exact_mut(mask_width).enumerate() {
+        for subscanline in 0..SUBSCANLINES {
+            // Find edge crossings on sub-scanline
+            let scan_y = (mask_y as usize + row_index) as Coordinate + (subscanline as Coordinate + 0.5) * SUBSCANLINE_WEIGHT;
+            while let Some(edge) = edges.get(next_edge).filter(|edge| edge.top.y <= scan_y) {
+                active_edges.push(edge);
+                next_edge += 1;
+            }
+            active_edges.retain(|edge| scan_y < edge.bottom.y);
+            crossings.clear();
+            crossings.extend(
+                active_edges.iter()
+                .map(|edge| (
+                    edge.top.x + (scan_y - edge.top.y) * (edge.bottom.x - edge.top.x) / (edge.bottom.y - edge.top.y) - mask_x as Coordinate,
+                    edge.winding
+                ))
+            );
+            crossings.sort_by(|crossing1, crossing2| crossing1.0.partial_cmp(&crossing2.0).unwrap_or(std::cmp::Ordering::Equal));
+            // Add spans with non-zero winding
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if winding != 0 {
+                    add_span(row, &mut full_coverage, pair[0].0, pair[1].0);
+                }
+            }
+        }
+        // Resolve spans of full pixels
+        let mut coverage = 0.0;
+        for (pixel, full_coverage) in row.iter_mut().zip(full_coverage.iter_mut()) {
+            coverage += *full_coverage;
+            *pixel = (*pixel + coverage).min(1.0);
+            *full_coverage = 0.0;
+        }
+        full_coverage[mask_width] = 0.0;
+    }
+    Some(mask)
+}
+
+// Lines of path without horizontal ones
+fn path_edges(path: &FlatPath) -> Vec<Edge> {
+    let mut edges = vec![];
+    let mut add_edge = |start: Point, end: Point| if start.y != end.y {
+        edges.push(if start.y < end.y {
+            Edge {top: start, bottom: end, winding: 1}
+        } else {
+            Edge {top: end, bottom: start, winding: -1}
+        });
+    };
+    let (mut sub_path_start, mut last_point): (Option<Point>, Option<Point>) = (None, None);
+    for segment in path.segments() {
+        match segment {
+            FlatPathSegment::MoveTo(point) => {
+                if let (Some(start), Some(last)) = (sub_path_start, last_point) {
+                    add_edge(last, start);
+                }
+                sub_path_start = Some(*point);
+                last_point = Some(*point);
+            }
+            FlatPathSegment::LineTo(point) => {
+                let start = *last_point.get_or_insert(Point::default());
+                sub_path_start.get_or_insert(start);
+                add_edge(start, *point);
+                last_point = Some(*point);
+            }
+            FlatPathSegment::Close => {
+                if let (Some(start), Some(last)) = (sub_path_start, last_point) {
+                    add_edge(last, start);
+                }
+                sub_path_start = None;
+                last_point = None;
+            }
+        }
+    }
+    if let (Some(start), Some(last)) = (sub_path_start, last_point) {
+        add_edge(last, start);
+    }
+    edges
+}
+
+// Add horizontal span of one sub-scanline to row coverage (partial pixels direct, full pixels as start/end deltas)
+fn add_span(row: &mut [f32], full_coverage: &mut [f32], start_x: Coordinate, end_x: Coordinate) {
+    let width = row.len() as Coordinate;
+    let (start_x, end_x) = (start_x.max(0.0).min(width), end_x.max(0.0).min(width));
+    if end_x <= start_x {
+        return;
+    }
+    let (start_pixel, end_pixel) = (start_x.floor() as usize, end_x.floor() as usize);
+    if start_pixel == end_pixel {
+        row[start_pixel] += (end_x - start_x) * SUBSCANLINE_WEIGHT;
+    } else {
+        row[start_pixel] += (start_pixel as Coordinate + 1.0 - start_x) * SUBSCANLINE_WEIGHT;
+        full_coverage[start_pixel + 1] += SUBSCANLINE_WEIGHT;
+        full_coverage[end_pixel] -= SUBSCANLINE_WEIGHT;
+        if end_pixel < row.len() {
+            row[end_pixel] += (end_x - end_pixel as Coordinate) * SUBSCANLINE_WEIGHT;
+        }
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{fill_path,FlatPath,PathBase,Point,Mask};
+
+    fn rectangle(path: &mut FlatPath, x0: f32, y0: f32, x1: f32, y1: f32) {
+        path.move_to(Point {x: x0, y: y0})
+            .line_to(Point {x: x1, y: y0})
+            .line_to(Point {x: x1, y: y1})
+            .line_to(Point {x: x0, y: y1});
+    }
+
+    #[test]
+    fn fill_pixel() {
+        let mut path = FlatPath::default();
+        rectangle(&mut path, 2.0, 3.0, 3.0, 4.0);
+        assert_eq!(fill_path(&path, 5, 5), Some(Mask {x: 2, y: 3, width: 1, height: 1, data: vec![1.0]}));
+        path.translate(-0.5, 0.5);
+        assert_eq!(fill_path(&path, 5, 5), Some(Mask {x: 1, y: 3, width: 2, height: 2, data: vec![0.25, 0.25, 0.25, 0.25]}));
+    }
+
+    #[test]
+    fn fill_non_zero() {
+        // Same direction overlap stays filled, opposite direction cuts hole
+        let mut path = FlatPath::default();
+        rectangle(&mut path, 0.0, 0.0, 3.0, 1.0);
+        rectangle(&mut path, 1.0, 0.0, 2.0, 1.0);
+        assert_eq!(fill_path(&path, 3, 1).map(|mask| mask.data), Some(vec![1.0, 1.0, 1.0]));
+        let mut path = FlatPath::default();
+        rectangle(&mut path, 0.0, 0.0, 3.0, 1.0);
+        rectangle(&mut path, 2.0, 0.0, 1.0, 1.0);
+        assert_eq!(fill_path(&path, 3, 1).map(|mask| mask.data), Some(vec![1.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn fill_edges_by_rows() {
+        // Edges become active & inactive by their rows, independent of path order
+        let mut path = FlatPath::default();
+        rectangle(&mut path, 0.0, 2.0, 1.0, 3.0);
+        rectangle(&mut path, 1.0, 0.0, 2.0, 1.0);
+        assert_eq!(fill_path(&path, 2, 3).map(|mask| mask.data), Some(vec![0.0, 1.0, 0.0, 0.0, 1.0, 0.0]));
+    }
+
+    #[test]
+    fn fill_clipped() {
+        let mut path = FlatPath::default();
+        path.move_to(Point {x: -5.0, y: -5.0})
+            .line_to(Point {x: 2.0, y: -5.0})
+            .line_to(Point {x: 2.0, y: 1.5})
+            .line_to(Point {x: -5.0, y: 1.5})
+            .close();
+        assert_eq!(fill_path(&path, 4, 4), Some(Mask {x: 0, y: 0, width: 2, height: 2, data: vec![1.0, 1.0, 0.5, 0.5]}));
+        path.translate(10.0, 10.0);
+        assert_eq!(fill_path(&path, 4, 4), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,67 @@
+// Imports
+use puny2d::raster::mask::Mask;
+use super::paint::Paint;
+
+
+/// Opacity buffer over image to clip drawing by previously drawn geometries.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Stencil {
+    width: u16,
+    height: u16,
+    data: Vec<f32>
+}
+impl Stencil {
+    /// Empty (transparent) stencil of image size.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0.0; width as usize * height as usize]
+        }
+    }
+    /// Adds coverage mask weighted by paint opacity (colors are ignored).
+    pub fn draw(&mut self, mask: &Mask, paint: &Paint) {
+        for (mask_y, row) in mask.data.chunks_exact(mask.width as usize).enumerate().take((self.height as usize).saturating_sub(mask.y as usize)) {
+            let stencil_row = (mask.y as usize + mask_y) * self.width as usize;
+            for (mask_x, coverage) in row.iter().enumerate().take((self.width as usize).saturating_sub(mask.x as usize)) {
+                if *coverage > 0.0 {
+                    let opacity = coverage.min(1.0) * paint.at(mask.x + mask_x as u16, mask.y + mask_y as u16).1;
+                    let sample = &mut self.data[stencil_row + mask.x as usize + mask_x];
+                    *sample += opacity * (1.0 - *sample);
+                }
+            }
+        }
+    }
+    /// Multiplies coverage mask by stencil opacity (or transparency when inverted).
+    pub fn clip(&self, mut mask: Mask, invert: bool) -> Mask {
+        for (mask_y, row) in mask.data.chunks_exact_mut(mask.width as usize).enumerate() {
+            for (mask_x, coverage) in row.iter_mut().enumerate() {
+                let (x, y) = (mask.x as usize + mask_x, mask.y as usize + mask_y);
+                let opacity = if x < self.width as usize && y < self.height as usize {self.data[y * self.width as usize + x]} else {0.0};
+                *coverage *= if invert {1.0 - opacity} else {opacity};
+            }
+        }
+        mask
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{Stencil,Mask,Paint};
+    use ssb_parser::objects::event_objects::{Color,Alpha};
+    use puny2d::vector::point::Point;
+
+    #[test]
+    fn stencil_draw_clip() {
+        let mut stencil = Stencil::new(4, 1);
+        let paint = |alpha: u8| Paint::new(&Color::Mono([0, 0, 0]), &Alpha::Mono(alpha), (Point::default(), Point::default()));
+        stencil.draw(&Mask {x: 1, y: 0, width: 4, height: 1, data: vec![1.0, 0.5, 0.0, 1.0]}, &paint(255));
+        stencil.draw(&Mask {x: 2, y: 0, width: 1, height: 1, data: vec![1.0]}, &paint(51));
+        assert_eq!(stencil.data, vec![0.0, 1.0, 0.6, 0.0]);
+        let mask = Mask {x: 0, y: 0, width: 4, height: 1, data: vec![1.0; 4]};
+        assert_eq!(stencil.clip(mask.clone(), false).data, vec![0.0, 1.0, 0.6, 0.0]);
+        assert_eq!(stencil.clip(mask, true).data, vec![1.0, 0.0, 0.39999998, 1.0]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,255 @@
+// Imports
+use ssb_parser::objects::event_objects::{Join,Cap};
+use puny2d::vector::{
+    types::Coordinate,
+    point::Point,
+    path::{PathBase,FlatPath,FlatPathSegment}
+};
+
+
+// Longest miter relative to pen size before falling back to bevel
+const MITER_LIMIT: Coordinate = 4.0;
+// Maximal distance of ellipse polygons to real curve (same as path flattening)
+const ELLIPSE_TOLERANCE: Coordinate = 0.125;
+
+/// Builds outline of path drawn by elliptical pen with radii per dimension.
+/// Output consists of equally oriented pieces (segments, joins & caps), so it has to be filled by non-zero winding rule.
+pub fn stroke_path(path: &FlatPath, radius_x: Coordinate, radius_y: Coordinate, join: &Join, cap: &Cap) -> FlatPath {
+    let mut outline = FlatPath::default();
+    let radius = (radius_x.max(0.0), radius_y.max(0.0));
+    if radius.0 <= 0.0 && radius.1 <= 0.0 {
+        return outline;
+    }
+    for (points, closed) in sub_paths(path) {
+        // Single point
+        if points.len() == 1 {
+            match cap {
+                Cap::Round => add_polygon(&mut outline, &ellipse(points[0], radius)),
+                Cap::Square => {
+                    let (point, (radius_x, radius_y)) = (points[0], radius);
+                    add_polygon(&mut outline, &[
+                        Point {x: point.x - radius_x, y: point.y - radius_y},
+                        Point {x: point.x + radius_x, y: point.y - radius_y},
+                        Point {x: point.x + radius_x, y: point.y + radius_y},
+                        Point {x: point.x - radius_x, y: point.y + radius_y}
+                    ]);
+                }
+                Cap::Butt => {}
+            }
+            continue;
+        }
+        // Segments
+        let segment_count = if closed {points.len()} else {points.len() - 1};
+        let segment = |index: usize| (points[index], points[(index + 1) % points.len()]);
+        for index in 0..segment_count {
+            let (start, end) = segment(index);
+            let offset = support(normal(direction(start, end)), radius);
+            add_polygon(&mut outline, &[start + offset, end + offset, end - offset, start - offset]);
+        }
+        // Joins
+        let joins = if closed {0..points.len()} else {1..points.len() - 1};
+        for index in joins {
+            let (previous, vertex) = segment((index + points.len() - 1) % points.len());
+            let next = segment(index).1;
+            add_join(&mut outline, vertex, direction(previous, vertex), direction(vertex, next), radius, join);
+        }
+        // Caps
+        if !closed {
+            let (first, last) = (points[0], points[points.len() - 1]);
+            add_cap(&mut outline, first, direction(points[1], first), radius, cap);
+            add_cap(&mut outline, last, direction(points[points.len() - 2], last), radius, cap);
+        }
+    }
+    outline
+}
+
+// Points of sub-paths without consecutive duplicates, flagged as closed
+fn sub_paths(path: &FlatPath) -> Vec<(Vec<Point>, bool)> {
+    let mut sub_paths: Vec<(Vec<Point>, bool)> = vec![];
+    let mut points: Vec<Point> = vec![];
+    let push_point = |points: &mut Vec<Point>, point: Point| if points.last() != Some(&point) {
+        points.push(point);
+    };
+    for segment in path.segments() {
+        match segment {
+            FlatPathSegment::MoveTo(point) => {
+                if !points.is_empty() {
+                    sub_paths.push((std::mem::take(&mut points), false));
+                }
+                points.push(*point);
+            }
+            FlatPathSegment::LineTo(point) => {
+                if points.is_empty() {
+                    points.push(sub_paths.last().map_or_else(Point::default, |(sub_path, _)| sub_path[0]));
+                }
+                push_point(&mut points, *point);
+            }
+            FlatPathSegment::Close => if !points.is_empty() {
+                if points.len() > 1 && points.first() == points.last() {
+                    points.pop();
+                }
+                let closed = points.len() > 2;
+                sub_paths.push((std::mem::take(&mut points), closed));
+            }
+        }
+    }
+    if !points.is_empty() {
+        sub_paths.push((points, false));
+    }
+    sub_paths
+}
+
+// Join piece on outer side of vertex
+fn add_join(outline: &mut FlatPath, vertex: Point, direction_in: Point, direction_out: Point, radius: (Coordinate, Coordinate), join: &Join) {
+    let turn = cross(direction_in, direction_out);
+    let reversal = turn == 0.0 && direction_in.x * direction_out.x + direction_in.y * direction_out.y < 0.0;
+    if turn == 0.0 && !reversal {
+        return;
+    }
+    if let Join::Round = join {
+        add_polygon(outline, &ellipse(vertex, radius));
+        return;
+    }
+    if reversal {
+        return;
+    }
+    // Outer side is opposite of turn
+    let side = if turn > 0.0 {-1.0} else {1.0};
+    let (outer_in, outer_out) = (
+        vertex + support(normal(direction_in), radius) * side,
+        vertex + support(normal(direction_out), radius) * side
+    );
+    if let Join::Miter = join {
+        let miter = outer_in + direction_in * (cross(outer_out - outer_in, direction_out) / turn);
+        let miter_length = ((miter.x - vertex.x).powi(2) + (miter.y - vertex.y).powi(2)).sqrt();
+        if miter_length <= MITER_LIMIT * radius.0.max(radius.1) {
+            add_polygon(outline, &[vertex, outer_in, miter, outer_out]);
+            return;
+        }
+    }
+    add_polygon(outline, &[vertex, outer_in, outer_out]);
+}
+
+// Cap piece at sub-path end, pointing in direction
+fn add_cap(outline: &mut FlatPath, end: Point, direction: Point, radius: (Coordinate, Coordinate), cap: &Cap) {
+    match cap {
+        Cap::Round => add_polygon(outline, &ellipse(end, radius)),
+        Cap::Square => {
+            let (offset, extension) = (support(normal(direction), radius), support(direction, radius));
+            add_polygon(outline, &[end + offset, end + offset + extension, end - offset + extension, end - offset]);
+        }
+        Cap::Butt => {}
+    }
+}
+
+// Adds closed polygon with positive orientation (degenerated ones get skipped)
+fn add_polygon(outline: &mut FlatPath, points: &[Point]) {
+    let area = points.iter().zip(points.iter().cycle().skip(1)).map(|(point, next)| cross(*point, *next)).sum::<Coordinate>();
+    if area == 0.0 {
+        return;
+    }
+    let mut points: Vec<Point> = points.to_vec();
+    if area < 0.0 {
+        points.reverse();
+    }
+    outline.move_to(points[0]);
+    for point in &points[1..] {
+        outline.line_to(*point);
+    }
+    outline.close();
+}
+
+// Polygon of ellipse, fine enough by tolerance
+fn ellipse(center: Point, radius: (Coordinate, Coordinate)) -> Vec<Point> {
+    let max_radius = radius.0.max(radius.1);
+    let step_angle = if max_radius > ELLIPSE_TOLERANCE {2.0 * (1.0 - ELLIPSE_TOLERANCE / max_radius).acos()} else {std::f32::consts::FRAC_PI_2};
+    let steps = ((std::f32::consts::PI * 2.0 / step_angle).ceil() as usize).max(4);
+    (0..steps)
+        .map(|step| {
+            let (sin, cos) = (step as Coordinate / steps as Coordinate * std::f32::consts::PI * 2.0).sin_cos();
+            Point {x: center.x + cos * radius.0, y: center.y + sin * radius.1}
+        })
+        .collect()
+}
+
+// Farthest point of ellipse (around origin) in direction
+fn support(direction: Point, radius: (Coordinate, Coordinate)) -> Point {
+    let (x, y) = (radius.0 * radius.0 * direction.x, radius.1 * radius.1 * direction.y);
+    let length = (x * direction.x + y * direction.y).sqrt();
+    if length > 0.0 {
+        Point {x: x / length, y: y / length}
+    } else {
+        Point::default()
+    }
+}
+
+// Unit vector between points
+fn direction(start: Point, end: Point) -> Point {
+    let (x, y) = (end.x - start.x, end.y - start.y);
+    let length = (x * x + y * y).sqrt();
+    Point {x: x / length, y: y / length}
+}
+
+// Perpendicular vector
+fn normal(direction: Point) -> Point {
+    Point {x: -direction.y, y: direction.x}
+}
+
+// Cross product (z-component)
+fn cross(vector1: Point, vector2: Point) -> Coordinate {
+    vector1.x * vector2.y - vector1.y * vector2.x
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{stroke_path,Join,Cap,FlatPath,PathBase,Point};
+    use crate::raster::scanline::fill_path;
+
+    fn line() -> FlatPath {
+        let mut path = FlatPath::default();
+        path.move_to(Point {x: 2.0, y: 5.0})
+            .line_to(Point {x: 8.0, y: 5.0});
+        path
+    }
+
+    #[test]
+    fn stroke_caps() {
+        let bounding = |cap: &Cap| stroke_path(&line(), 1.0, 2.0, &Join::Round, cap).bounding();
+        assert_eq!(bounding(&Cap::Butt), Some((Point {x: 2.0, y: 3.0}, Point {x: 8.0, y: 7.0})));
+        assert_eq!(bounding(&Cap::Square), Some((Point {x: 1.0, y: 3.0}, Point {x: 9.0, y: 7.0})));
+        assert_eq!(stroke_path(&line(), 0.0, -1.0, &Join::Round, &Cap::Round).bounding(), None);
+    }
+
+    #[test]
+    fn stroke_joins() {
+        // Right angle: miter reaches corner, bevel cuts it, round lies between
+        let mut path = FlatPath::default();
+        path.move_to(Point {x: 2.0, y: 2.0})
+            .line_to(Point {x: 8.0, y: 2.0})
+            .line_to(Point {x: 8.0, y: 8.0});
+        let corner_coverage = |join: &Join| {
+            let mask = fill_path(&stroke_path(&path, 1.0, 1.0, join, &Cap::Butt), 10, 10).expect("Stroke should be visible!");
+            mask.data[(1 - mask.y as usize) * mask.width as usize + (8 - mask.x as usize)]
+        };
+        let (miter, round, bevel) = (corner_coverage(&Join::Miter), corner_coverage(&Join::Round), corner_coverage(&Join::Bevel));
+        assert_eq!(miter, 1.0);
+        assert!(miter > round && round > bevel && bevel > 0.0, "{} > {} > {}", miter, round, bevel);
+    }
+
+    #[test]
+    fn stroke_closed() {
+        // Ring around square without hole in stroke
+        let mut path = FlatPath::default();
+        path.move_to(Point {x: 2.0, y: 2.0})
+            .line_to(Point {x: 8.0, y: 2.0})
+            .line_to(Point {x: 8.0, y: 8.0})
+            .line_to(Point {x: 2.0, y: 8.0})
+            .close();
+        let mask = fill_path(&stroke_path(&path, 1.0, 1.0, &Join::Miter, &Cap::Butt), 10, 10).expect("Stroke should be visible!");
+        assert_eq!((mask.x, mask.y, mask.width, mask.height), (1, 1, 8, 8));
+        assert!(mask.data[..8].iter().all(|coverage| *coverage == 1.0));
+        assert_eq!(mask.data[4 * 8 + 4], 0.0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,149 @@
+// Imports
+use ssb_parser::objects::event_objects::TextureWrapping;
+use png::{Decoder,ColorType,Transformations};
+use crate::error::RenderingError;
+
+
+/// Decoded image with straight RGBA pixels (8 bit per channel, rows from top to bottom).
+#[derive(Debug, PartialEq, Clone)]
+pub struct Texture {
+    width: u32,
+    height: u32,
+    data: Vec<[u8;4]>
+}
+impl Texture {
+    /// Decodes image file data (PNG).
+    pub fn decode(data: &[u8]) -> Result<Self, RenderingError> {
+        let mut decoder = Decoder::new(data);
+        decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+        let (info, mut reader) = decoder.read_info().map_err(|err| RenderingError::new_with_source("Couldn't read PNG header!", err))?;
+        let pixels = (info.width as usize).checked_mul(info.height as usize).ok_or_else(|| RenderingError::new("PNG image too large!"))?;
+        let mut buffer = vec![0; info.buffer_size()];
+        reader.next_frame(&mut buffer).map_err(|err| RenderingError::new_with_source("Couldn't read PNG image!", err))?;
+        let (color_type, _) = reader.output_color_type();
+        let data: Vec<[u8;4]> = match color_type {
+            ColorType::Grayscale => buffer.iter().map(|gray| [*gray, *gray, *gray, u8::MAX]).take(pixels).collect(),
+            ColorType::GrayscaleAlpha => buffer.chunks_exact(2).map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]]).take(pixels).collect(),
+            ColorType::RGB => buffer.chunks_exact(3).map(|pixel| [pixel[0], pixel[1], pixel[2], u8::MAX]).take(pixels).collect(),
+            ColorType::RGBA => buffer.chunks_exact(4).map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]]).take(pixels).collect(),
+            ColorType::Indexed => return Err(RenderingError::new("PNG palette wasn't expanded!"))
+        };
+        if data.len() != pixels || pixels == 0 {
+            return Err(RenderingError::new("PNG image data incomplete!"));
+        }
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            data
+        })
+    }
+    /// Texture width.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    /// Texture height.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    /// Bilinear filtered color (channels 0-255) & opacity (0-1) at texture position (in pixels, texel centers at +0.5).
+    /// Wrapping decides about positions outside: transparent (pad), edge pixels (clamp), tiles (repeat) or mirrored tiles (mirror).
+    pub fn sample(&self, x: f32, y: f32, wrap: &TextureWrapping) -> ([f32;3], f32) {
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (column, row) = (x.floor(), y.floor());
+        let (weight_x, weight_y) = (x - column, y - row);
+        // Interpolate premultiplied to keep transparent texels colorless
+        let mut premultiplied = [0.0f32; 4];
+        for (texel_column, texel_row, weight) in [
+            (column, row, (1.0 - weight_x) * (1.0 - weight_y)),
+            (column + 1.0, row, weight_x * (1.0 - weight_y)),
+            (column, row + 1.0, (1.0 - weight_x) * weight_y),
+            (column + 1.0, row + 1.0, weight_x * weight_y)
+        ] {
+            if weight <= 0.0 {
+                continue;
+            }
+            if let (Some(texel_column), Some(texel_row)) = (wrap_index(texel_column as i64, self.width, wrap), wrap_index(texel_row as i64, self.height, wrap)) {
+                let texel = self.data[texel_row as usize * self.width as usize + texel_column as usize];
+                let alpha = texel[3] as f32 / u8::MAX as f32 * weight;
+                for (channel, texel_channel) in premultiplied.iter_mut().zip(&texel[..3]) {
+                    *channel += *texel_channel as f32 * alpha;
+                }
+                premultiplied[3] += alpha;
+            }
+        }
+        let alpha = premultiplied[3];
+        if alpha > 0.0 {
+            ([premultiplied[0] / alpha, premultiplied[1] / alpha, premultiplied[2] / alpha], alpha.min(1.0))
+        } else {
+            ([0.0; 3], 0.0)
+        }
+    }
+}
+
+// Texel index inside texture dimension by wrapping (none for transparent padding)
+fn wrap_index(index: i64, size: u32, wrap: &TextureWrapping) -> Option<u32> {
+    let size = size as i64;
+    match wrap {
+        TextureWrapping::Pad => if (0..size).contains(&index) {Some(index as u32)} else {None},
+        TextureWrapping::Clamp => Some(index.clamp(0, size - 1) as u32),
+        TextureWrapping::Repeat => Some(index.rem_euclid(size) as u32),
+        TextureWrapping::Mirror => {
+            let index = index.rem_euclid(size * 2);
+            Some(if index < size {index} else {size * 2 - 1 - index} as u32)
+        }
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{Texture,TextureWrapping,wrap_index};
+
+    fn checker() -> Texture {
+        Texture {
+            width: 2,
+            height: 2,
+            data: vec![[255, 0, 0, 255], [0, 0, 255, 255], [0, 0, 255, 255], [0, 255, 0, 0]]
+        }
+    }
+
+    #[test]
+    fn texture_wrapping() {
+        assert_eq!((wrap_index(-1, 3, &TextureWrapping::Pad), wrap_index(2, 3, &TextureWrapping::Pad)), (None, Some(2)));
+        assert_eq!((wrap_index(-1, 3, &TextureWrapping::Clamp), wrap_index(7, 3, &TextureWrapping::Clamp)), (Some(0), Some(2)));
+        assert_eq!((wrap_index(-1, 3, &TextureWrapping::Repeat), wrap_index(7, 3, &TextureWrapping::Repeat)), (Some(2), Some(1)));
+        assert_eq!((wrap_index(-1, 3, &TextureWrapping::Mirror), wrap_index(4, 3, &TextureWrapping::Mirror), wrap_index(6, 3, &TextureWrapping::Mirror)), (Some(0), Some(1), Some(0)));
+    }
+
+    #[test]
+    fn texture_sample() {
+        let texture = checker();
+        // Texel centers
+        assert_eq!(texture.sample(0.5, 0.5, &TextureWrapping::Pad), ([255.0, 0.0, 0.0], 1.0));
+        assert_eq!(texture.sample(1.5, 0.5, &TextureWrapping::Pad), ([0.0, 0.0, 255.0], 1.0));
+        // Bilinear filtering without color of transparent texel
+        assert_eq!(texture.sample(1.0, 0.5, &TextureWrapping::Pad), ([127.5, 0.0, 127.5], 1.0));
+        assert_eq!(texture.sample(1.5, 1.0, &TextureWrapping::Pad), ([0.0, 0.0, 255.0], 0.5));
+        // Outside
+        assert_eq!(texture.sample(-0.5, 0.5, &TextureWrapping::Pad), ([0.0; 3], 0.0));
+        assert_eq!(texture.sample(-0.5, 0.5, &TextureWrapping::Clamp), ([255.0, 0.0, 0.0], 1.0));
+        assert_eq!(texture.sample(-0.5, 0.5, &TextureWrapping::Repeat), ([0.0, 0.0, 255.0], 1.0));
+        assert_eq!(texture.sample(-0.5, 0.5, &TextureWrapping::Mirror), ([255.0, 0.0, 0.0], 1.0));
+    }
+
+    #[test]
+    fn texture_decode() {
+        let mut png_data = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut png_data, 2, 1);
+            encoder.set_color(png::ColorType::GrayscaleAlpha);
+            encoder.write_header().and_then(|mut writer| writer.write_image_data(&[0, 255, 200, 100]))
+                .expect("PNG encoding shouldn't fail!");
+        }
+        let texture = Texture::decode(&png_data).expect("PNG should be decodable!");
+        assert_eq!((texture.width(), texture.height()), (2, 1));
+        assert_eq!(texture.data, vec![[0, 0, 0, 255], [200, 200, 200, 100]]);
+        assert!(Texture::decode(b"No image").is_err());
+    }
+}
\ No newline at end of file
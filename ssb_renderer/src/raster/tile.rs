@@ -0,0 +1,198 @@
+// Imports
+use std::ops::Range;
+use rayon::prelude::*;
+use puny2d::{
+    raster::{
+        image::ImageView,
+        mask::Mask
+    },
+    vector::{
+        point::Point,
+        path::FlatPath
+    }
+};
+use super::{
+    path::map_flat_path,
+    scanline::fill_path,
+    blur::{blur_mask,blur_extent}
+};
+
+
+/// Minimal image rows per tile (smaller images aren't divided).
+pub const MIN_TILE_HEIGHT: u16 = 128;
+
+/// Draws on horizontal image tiles in parallel, one per thread (as tiles share work on their borders, f.e. blur).
+/// Draw function gets a tile view and its rows in image.
+pub fn for_each_tile<F>(img: &mut ImageView, draw: F)
+    where F: Fn(ImageView, Range<u16>) + Sync {
+    let (width, height, stride, color_type) = (img.width(), img.height(), img.stride() as usize, img.color_type());
+    let tile_height = ((height as f32 / rayon::current_num_threads() as f32).ceil() as usize).max(MIN_TILE_HEIGHT as usize);
+    let tile_size = tile_height * stride;
+    // Borrow planes out of image to split them by rows
+    let mut planes = (0..color_type.planes())
+        .map(|index| std::mem::take(img.plane_mut(index).expect("Plane should exist by color type!")))
+        .collect::<Vec<_>>();
+    {
+        let mut tiles = (0..height as usize).step_by(tile_height).map(|_| vec![]).collect::<Vec<_>>();
+        for plane in planes.iter_mut() {
+            for (tile, rows) in tiles.iter_mut().zip(plane[..height as usize * stride].chunks_mut(tile_size)) {
+                tile.push(rows);
+            }
+        }
+        tiles.into_par_iter().enumerate().for_each(|(index, tile)| {
+            let rows = (index * tile_height) as u16..((index + 1) * tile_height).min(height as usize) as u16;
+            draw(
+                ImageView::new(width, rows.end - rows.start, stride as u32, color_type, tile).expect("Tile should fit into image!"),
+                rows
+            );
+        });
+    }
+    for (index, plane) in planes.into_iter().enumerate() {
+        *img.plane_mut(index as u8).expect("Plane should exist by color type!") = plane;
+    }
+}
+
+/// Fills path (in image of given size) into coverage mask of tile rows, blurred like the whole image would be.
+/// Mask is relative to tile.
+pub fn fill_tile(path: &FlatPath, width: u16, height: u16, rows: &Range<u16>, blur: (f32, f32)) -> Option<Mask> {
+    // Neighbour rows blur into tile
+    let extent = blur_extent(blur.1).min(u16::MAX as usize) as u16;
+    let (top, bottom) = (rows.start.saturating_sub(extent), rows.end.saturating_add(extent).min(height));
+    let region_path = map_flat_path(path, |point| Point {x: point.x, y: point.y - top as f32});
+    let mask = blur_mask(fill_path(&region_path, width, bottom - top)?, blur.0, blur.1, width, bottom - top);
+    // Crop region to tile
+    let (crop_top, crop_bottom) = ((rows.start - top).max(mask.y), (rows.end - top).min(mask.y + mask.height));
+    if crop_bottom <= crop_top {
+        return None;
+    }
+    let mask_width = mask.width as usize;
+    Some(Mask {
+        x: mask.x,
+        y: crop_top - (rows.start - top),
+        width: mask.width,
+        height: crop_bottom - crop_top,
+        data: mask.data[(crop_top - mask.y) as usize * mask_width..(crop_bottom - mask.y) as usize * mask_width].to_vec()
+    })
+}
+
+/// Moves mask by offset and crops it to area of given size.
+pub fn place_mask(mask: &Mask, offset: (i32, i32), width: u16, height: u16) -> Option<Mask> {
+    let (x, y) = (mask.x as i32 + offset.0, mask.y as i32 + offset.1);
+    let (left, top) = (x.max(0), y.max(0));
+    let (right, bottom) = ((x + mask.width as i32).min(width as i32), (y + mask.height as i32).min(height as i32));
+    if right <= left || bottom <= top {
+        return None;
+    }
+    let mask_width = mask.width as usize;
+    Some(Mask {
+        x: left as u16,
+        y: top as u16,
+        width: (right - left) as u16,
+        height: (bottom - top) as u16,
+        data: (top..bottom)
+            .flat_map(|row| {
+                let row_start = (row - y) as usize * mask_width;
+                mask.data[row_start + (left - x) as usize..row_start + (right - x) as usize].iter().copied()
+            })
+            .collect()
+    })
+}
+
+
+/// Removes coverage of cut mask (drawn on top later) from mask, both relative to same area.
+/// Mask keeps its coverage where cut one leaves space, so antialiased seams between both stay closed.
+pub fn cut_mask(mask: &mut Mask, cut: &Mask) {
+    let (mask_width, cut_width) = (mask.width as usize, cut.width as usize);
+    for (mask_y, row) in mask.data.chunks_exact_mut(mask_width).enumerate() {
+        let y = mask.y as usize + mask_y;
+        if y < cut.y as usize || y >= (cut.y + cut.height) as usize {
+            continue;
+        }
+        let cut_row = &cut.data[(y - cut.y as usize) * cut_width..(y - cut.y as usize + 1) * cut_width];
+        for (mask_x, coverage) in row.iter_mut().enumerate() {
+            let x = mask.x as usize + mask_x;
+            if x >= cut.x as usize && x < (cut.x + cut.width) as usize {
+                let space = 1.0 - cut_row[x - cut.x as usize].min(1.0);
+                *coverage = if space > 0.0 {coverage.min(space) / space} else {0.0};
+            }
+        }
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{for_each_tile,fill_tile,place_mask,cut_mask,ImageView,Mask,FlatPath,Point,MIN_TILE_HEIGHT};
+    use puny2d::{
+        raster::image::ColorType,
+        vector::path::PathBase
+    };
+    use crate::raster::{
+        scanline::fill_path,
+        blur::blur_mask
+    };
+
+    #[test]
+    fn tile_rows() {
+        let (width, height) = (3, MIN_TILE_HEIGHT * 8 + 5);
+        // Blue plane filled by row index, image planes restored
+        let (mut red, mut green, mut blue) = (vec![0u8; width as usize * height as usize], vec![0u8; width as usize * height as usize], vec![0u8; width as usize * height as usize]);
+        let mut img = ImageView::new(width, height, width as u32, ColorType::R8G8B8, vec![&mut red, &mut green, &mut blue]).expect("ImageView must've valid dimensions!");
+        for_each_tile(&mut img, |mut tile, rows| {
+            assert_eq!(tile.height(), rows.end - rows.start);
+            for (y, row) in tile.plane_rows_mut(2).expect("Plane should exist!").enumerate() {
+                row.iter_mut().for_each(|sample| *sample = ((rows.start as usize + y) % 256) as u8);
+            }
+        });
+        assert_eq!(img.plane(2).map(|plane| plane.len()), Some(width as usize * height as usize));
+        assert_eq!(
+            img.plane_rows(2).expect("Plane should exist!").map(|row| row[0]).collect::<Vec<_>>(),
+            (0..height).map(|y| (y % 256) as u8).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn tile_fill_blurred() {
+        let mut path = FlatPath::default();
+        path.move_to(Point {x: 2.0, y: 100.0});
+        path.line_to(Point {x: 8.0, y: 100.0});
+        path.line_to(Point {x: 8.0, y: 150.0});
+        path.line_to(Point {x: 2.0, y: 150.0});
+        path.close();
+        let (width, height, blur) = (10, 200, (1.0, 4.0));
+        let whole = blur_mask(fill_path(&path, width, height).expect("Path should be visible!"), blur.0, blur.1, width, height);
+        // Tiles combined equal whole image
+        let mut rows = vec![vec![0.0; width as usize]; height as usize];
+        for tile_rows in [0..64, 64..128, 128..200] {
+            if let Some(mask) = fill_tile(&path, width, height, &tile_rows, blur) {
+                for (mask_y, mask_row) in mask.data.chunks_exact(mask.width as usize).enumerate() {
+                    rows[(tile_rows.start + mask.y) as usize + mask_y][mask.x as usize..(mask.x + mask.width) as usize].copy_from_slice(mask_row);
+                }
+            }
+        }
+        for (mask_y, mask_row) in whole.data.chunks_exact(whole.width as usize).enumerate() {
+            let row = &rows[whole.y as usize + mask_y][whole.x as usize..(whole.x + whole.width) as usize];
+            assert!(row.iter().zip(mask_row).all(|(tiled, whole)| (tiled - whole).abs() < 1e-4), "Row {} differs!", whole.y as usize + mask_y);
+        }
+        assert!(fill_tile(&path, width, height, &(0..64), blur).is_none());
+    }
+
+    #[test]
+    fn tile_place_mask() {
+        let mask = Mask {x: 1, y: 1, width: 3, height: 2, data: vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]};
+        assert_eq!(place_mask(&mask, (2, 3), 8, 8), Some(Mask {x: 3, y: 4, width: 3, height: 2, data: mask.data.clone()}));
+        assert_eq!(place_mask(&mask, (-2, -2), 2, 8), Some(Mask {x: 0, y: 0, width: 2, height: 1, data: vec![0.5, 0.6]}));
+        assert!(place_mask(&mask, (8, 0), 8, 8).is_none());
+    }
+
+    #[test]
+    fn tile_cut_mask() {
+        // Covered part removed, partial coverage raised to fill space left by cut
+        let mut mask = Mask {x: 0, y: 1, width: 4, height: 1, data: vec![1.0, 1.0, 0.25, 1.0]};
+        cut_mask(&mut mask, &Mask {x: 1, y: 1, width: 2, height: 1, data: vec![1.0, 0.5]});
+        assert_eq!(mask.data, vec![1.0, 0.0, 0.5, 1.0]);
+        // Cut beside mask rows changes nothing
+        cut_mask(&mut mask, &Mask {x: 0, y: 0, width: 4, height: 1, data: vec![1.0; 4]});
+        assert_eq!(mask.data, vec![1.0, 0.0, 0.5, 1.0]);
+    }
+}
\ No newline at end of file
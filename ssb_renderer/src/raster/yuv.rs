@@ -0,0 +1,324 @@
+// Imports
+use crate::error::RenderingError;
+
+
+/// Resolution of chroma planes relative to luma plane.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ChromaSubsampling {
+    /// Half width & half height.
+    Yuv420,
+    /// Half width.
+    Yuv422,
+    /// Full resolution.
+    Yuv444
+}
+impl ChromaSubsampling {
+    /// Horizontal & vertical shift from luma to chroma coordinates.
+    pub fn shifts(self) -> (u8, u8) {
+        match self {
+            Self::Yuv420 => (1, 1),
+            Self::Yuv422 => (1, 0),
+            Self::Yuv444 => (0, 0)
+        }
+    }
+}
+
+/// Matrix for conversion between RGB and YUV.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ColorMatrix {
+    BT601,
+    BT709,
+    BT2020
+}
+impl ColorMatrix {
+    /// Luma weights of red & blue.
+    pub fn coefficients(self) -> (f32, f32) {
+        match self {
+            Self::BT601 => (0.299, 0.114),
+            Self::BT709 => (0.2126, 0.0722),
+            Self::BT2020 => (0.2627, 0.0593)
+        }
+    }
+    /// Get variant by name.
+    pub fn by_name(name: &str) -> Result<Self, RenderingError> {
+        match name.to_uppercase().as_str() {
+            "BT601" => Ok(Self::BT601),
+            "BT709" => Ok(Self::BT709),
+            "BT2020" => Ok(Self::BT2020),
+            _ => Err(RenderingError::new(&format!("'{}' isn't a valid color matrix!", name)))
+        }
+    }
+}
+
+/// Layout & color space of planar YUV image data.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct YuvFormat {
+    pub subsampling: ChromaSubsampling,
+    /// Bits per sample (8-16), stored in 1 byte up to 8 bits, else 2 bytes (native endian).
+    pub bits: u8,
+    pub matrix: ColorMatrix,
+    /// Samples use full value range instead of limited (TV) range.
+    pub full_range: bool
+}
+impl YuvFormat {
+    /// Get format by name of subsampling & bits (f.e. "YUV420P10"), color space given separately.
+    pub fn by_name(name: &str, matrix: ColorMatrix, full_range: bool) -> Result<Self, RenderingError> {
+        let invalid = || RenderingError::new(&format!("'{}' isn't a valid YUV format!", name));
+        let name = name.to_uppercase();
+        let (subsampling, bits) = name.strip_prefix("YUV")
+            .and_then(|name| name.split_once('P'))
+            .ok_or_else(invalid)?;
+        Ok(Self {
+            subsampling: match subsampling {
+                "420" => ChromaSubsampling::Yuv420,
+                "422" => ChromaSubsampling::Yuv422,
+                "444" => ChromaSubsampling::Yuv444,
+                _ => return Err(invalid())
+            },
+            bits: bits.parse().ok().filter(|bits| (8..=16).contains(bits)).ok_or_else(invalid)?,
+            matrix,
+            full_range
+        })
+    }
+    /// Size of one sample in bytes.
+    pub fn sample_size(&self) -> u8 {
+        if self.bits > 8 {2} else {1}
+    }
+    /// Width & height of plane (0 = luma, 1 = Cb, 2 = Cr) for image size.
+    pub fn plane_size(&self, index: u8, width: u16, height: u16) -> (u16, u16) {
+        if index == 0 {
+            (width, height)
+        } else {
+            let (shift_x, shift_y) = self.subsampling.shifts();
+            (((width as u32 + (1 << shift_x) - 1) >> shift_x) as u16, ((height as u32 + (1 << shift_y) - 1) >> shift_y) as u16)
+        }
+    }
+    /// Luma, Cb & Cr samples of RGB color (channels 0-255).
+    pub fn from_rgb(&self, color: [f32;3]) -> [f32;3] {
+        let (kr, kb) = self.matrix.coefficients();
+        let [r, g, b] = [color[0] / u8::MAX as f32, color[1] / u8::MAX as f32, color[2] / u8::MAX as f32];
+        let y = kr * r + (1.0 - kr - kb) * g + kb * b;
+        let (cb, cr) = ((b - y) / (2.0 * (1.0 - kb)), (r - y) / (2.0 * (1.0 - kr)));
+        let max = ((1u32 << self.bits) - 1) as f32;
+        let samples = if self.full_range {
+            let center = (1u32 << (self.bits - 1)) as f32;
+            [y * max, center + cb * max, center + cr * max]
+        } else {
+            let scale = (1u32 << (self.bits - 8)) as f32;
+            [(16.0 + 219.0 * y) * scale, (128.0 + 224.0 * cb) * scale, (128.0 + 224.0 * cr) * scale]
+        };
+        [samples[0].clamp(0.0, max), samples[1].clamp(0.0, max), samples[2].clamp(0.0, max)]
+    }
+    /// RGB color (channels 0-255) of luma, Cb & Cr samples.
+    pub fn to_rgb(&self, samples: [f32;3]) -> [f32;3] {
+        let (kr, kb) = self.matrix.coefficients();
+        let (y, cb, cr) = if self.full_range {
+            let (max, center) = (((1u32 << self.bits) - 1) as f32, (1u32 << (self.bits - 1)) as f32);
+            (samples[0] / max, (samples[1] - center) / max, (samples[2] - center) / max)
+        } else {
+            let scale = (1u32 << (self.bits - 8)) as f32;
+            ((samples[0] / scale - 16.0) / 219.0, (samples[1] / scale - 128.0) / 224.0, (samples[2] / scale - 128.0) / 224.0)
+        };
+        let (r, b) = (y + 2.0 * (1.0 - kr) * cr, y + 2.0 * (1.0 - kb) * cb);
+        let g = (y - kr * r - kb * b) / (1.0 - kr - kb);
+        let channel = |value: f32| (value * u8::MAX as f32).clamp(0.0, u8::MAX as f32);
+        [channel(r), channel(g), channel(b)]
+    }
+}
+
+/// Reference on planar YUV image data (luma, Cb & Cr planes) with meta information.
+#[derive(Debug, PartialEq)]
+pub struct YuvImageView<'data> {
+    width: u16,
+    height: u16,
+    format: YuvFormat,
+    strides: [u32;3],
+    planes: Vec<&'data mut [u8]>
+}
+impl<'data> YuvImageView<'data> {
+    /// New image view on given data, strides (in bytes) per plane.
+    pub fn new(width: u16, height: u16, format: YuvFormat, strides: [u32;3], planes: Vec<&'data mut [u8]>) -> Result<Self, RenderingError> {
+        if !(8..=16).contains(&format.bits) {
+            return Err(RenderingError::new("Bits per sample must be between 8 and 16!"));
+        }
+        if planes.len() != 3 {
+            return Err(RenderingError::new("Number of planes must be 3!"));
+        }
+        for (index, (plane, stride)) in planes.iter().zip(&strides).enumerate() {
+            let (plane_width, plane_height) = format.plane_size(index as u8, width, height);
+            if (*stride as usize) < plane_width as usize * format.sample_size() as usize {
+                return Err(RenderingError::new("Stride must at least cover row size!"));
+            }
+            if plane.len() < *stride as usize * plane_height as usize {
+                return Err(RenderingError::new("At least one plane isn't big enough for expected data size!"));
+            }
+        }
+        Ok(Self {
+            width,
+            height,
+            format,
+            strides,
+            planes
+        })
+    }
+    /// Get image width.
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+    /// Get image height.
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+    /// Get image format.
+    pub fn format(&self) -> YuvFormat {
+        self.format
+    }
+    /// RGB planes of image size (chroma samples cover all their luma pixels).
+    pub fn to_rgb(&self) -> [Vec<u8>;3] {
+        let (width, height, format) = (self.width as usize, self.height as usize, self.format);
+        let sample_size = format.sample_size() as usize;
+        let (shift_x, shift_y) = format.subsampling.shifts();
+        let mut rgb = [vec![0u8; width * height], vec![0u8; width * height], vec![0u8; width * height]];
+        for y in 0..height {
+            for x in 0..width {
+                let chroma_offset = |plane: usize| (y >> shift_y) * self.strides[plane] as usize + (x >> shift_x) * sample_size;
+                let color = format.to_rgb([
+                    read_sample(self.planes[0], y * self.strides[0] as usize + x * sample_size, sample_size),
+                    read_sample(self.planes[1], chroma_offset(1), sample_size),
+                    read_sample(self.planes[2], chroma_offset(2), sample_size)
+                ]);
+                for (plane, channel) in rgb.iter_mut().zip(&color) {
+                    plane[y * width + x] = channel.round() as u8;
+                }
+            }
+        }
+        rgb
+    }
+    /// Composes straight RGBA overlay (planes of image size) over image.
+    /// Chroma samples take the mean of their luma pixels (box filtered, premultiplied by alpha).
+    pub fn compose(&mut self, overlay: [&[u8];4]) {
+        let (width, height, format) = (self.width as usize, self.height as usize, self.format);
+        let sample_size = format.sample_size() as usize;
+        let [red, green, blue, alpha] = overlay;
+        let color = |index: usize| format.from_rgb([red[index] as f32, green[index] as f32, blue[index] as f32]);
+        // Luma
+        for y in 0..height {
+            for x in (0..width).filter(|x| alpha[y * width + x] > 0) {
+                let index = y * width + x;
+                let opacity = alpha[index] as f32 / u8::MAX as f32;
+                mix_sample(self.planes[0], y * self.strides[0] as usize + x * sample_size, sample_size, color(index)[0], opacity);
+            }
+        }
+        // Chroma
+        let (shift_x, shift_y) = format.subsampling.shifts();
+        let (plane_width, plane_height) = format.plane_size(1, self.width, self.height);
+        for chroma_y in 0..plane_height as usize {
+            for chroma_x in 0..plane_width as usize {
+                let (mut opacity_sum, mut chroma_sums, mut count) = (0.0, [0.0; 2], 0);
+                for y in (chroma_y << shift_y..(chroma_y + 1) << shift_y).take_while(|y| *y < height) {
+                    for x in (chroma_x << shift_x..(chroma_x + 1) << shift_x).take_while(|x| *x < width) {
+                        let index = y * width + x;
+                        count += 1;
+                        if alpha[index] > 0 {
+                            let opacity = alpha[index] as f32 / u8::MAX as f32;
+                            let [_, cb, cr] = color(index);
+                            opacity_sum += opacity;
+                            chroma_sums[0] += cb * opacity;
+                            chroma_sums[1] += cr * opacity;
+                        }
+                    }
+                }
+                if opacity_sum > 0.0 {
+                    let offset = chroma_y * self.strides[1] as usize + chroma_x * sample_size;
+                    mix_sample(self.planes[1], offset, sample_size, chroma_sums[0] / opacity_sum, opacity_sum / count as f32);
+                    let offset = chroma_y * self.strides[2] as usize + chroma_x * sample_size;
+                    mix_sample(self.planes[2], offset, sample_size, chroma_sums[1] / opacity_sum, opacity_sum / count as f32);
+                }
+            }
+        }
+    }
+}
+
+// Sample (1 or 2 bytes) at plane offset
+fn read_sample(plane: &[u8], offset: usize, sample_size: usize) -> f32 {
+    if sample_size == 2 {
+        u16::from_ne_bytes([plane[offset], plane[offset + 1]]) as f32
+    } else {
+        plane[offset] as f32
+    }
+}
+
+// Mixes sample (1 or 2 bytes) at plane offset with value by opacity
+fn mix_sample(plane: &mut [u8], offset: usize, sample_size: usize, value: f32, opacity: f32) {
+    let sample = read_sample(plane, offset, sample_size);
+    let mixed = sample + (value - sample) * opacity;
+    if sample_size == 2 {
+        plane[offset..offset + 2].copy_from_slice(&(mixed.round().clamp(0.0, u16::MAX as f32) as u16).to_ne_bytes());
+    } else {
+        plane[offset] = mixed.round().clamp(0.0, u8::MAX as f32) as u8;
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{YuvImageView,YuvFormat,ColorMatrix,ChromaSubsampling};
+
+    #[test]
+    fn yuv_format_by_name() {
+        let format = YuvFormat::by_name("yuv420p10", ColorMatrix::BT709, false).expect("Format name should be valid!");
+        assert_eq!((format.subsampling, format.bits, format.sample_size()), (ChromaSubsampling::Yuv420, 10, 2));
+        assert_eq!(YuvFormat::by_name("YUV444P8", ColorMatrix::BT601, true).map(|format| format.subsampling).ok(), Some(ChromaSubsampling::Yuv444));
+        assert!(YuvFormat::by_name("YUV411P8", ColorMatrix::BT601, false).is_err());
+        assert!(YuvFormat::by_name("YUV420P7", ColorMatrix::BT601, false).is_err());
+        assert!(YuvFormat::by_name("RGB24", ColorMatrix::BT601, false).is_err());
+        assert_eq!(ColorMatrix::by_name("bt2020").ok(), Some(ColorMatrix::BT2020));
+        assert_eq!(format.plane_size(1, 5, 3), (3, 2));
+    }
+
+    #[test]
+    fn yuv_from_rgb() {
+        let limited = YuvFormat::by_name("YUV444P8", ColorMatrix::BT709, false).expect("Format name should be valid!");
+        let round = |values: [f32;3]| [values[0].round(), values[1].round(), values[2].round()];
+        assert_eq!(round(limited.from_rgb([255.0, 255.0, 255.0])), [235.0, 128.0, 128.0]);
+        assert_eq!(round(limited.from_rgb([0.0, 0.0, 0.0])), [16.0, 128.0, 128.0]);
+        assert_eq!(round(limited.from_rgb([255.0, 0.0, 0.0])), [63.0, 102.0, 240.0]);
+        let full = YuvFormat {bits: 10, full_range: true, matrix: ColorMatrix::BT601, ..limited};
+        assert_eq!(round(full.from_rgb([255.0, 255.0, 255.0])), [1023.0, 512.0, 512.0]);
+        assert_eq!(round(full.from_rgb([0.0, 0.0, 255.0]))[1], 1023.0);
+    }
+
+    #[test]
+    fn yuv_to_rgb() {
+        let round = |values: [f32;3]| [values[0].round(), values[1].round(), values[2].round()];
+        for format in [
+            YuvFormat::by_name("YUV444P8", ColorMatrix::BT709, false).expect("Format name should be valid!"),
+            YuvFormat::by_name("YUV444P10", ColorMatrix::BT2020, true).expect("Format name should be valid!")
+        ] {
+            for color in [[255.0, 128.0, 0.0], [0.0, 0.0, 0.0], [10.0, 200.0, 255.0]] {
+                assert_eq!(round(format.to_rgb(format.from_rgb(color))), color);
+            }
+        }
+        let format = YuvFormat::by_name("YUV420P8", ColorMatrix::BT601, true).expect("Format name should be valid!");
+        let (mut luma, mut cb, mut cr) = (vec![255u8, 0, 255, 0], vec![128u8], vec![128u8]);
+        let img = YuvImageView::new(2, 2, format, [2, 1, 1], vec![&mut luma, &mut cb, &mut cr]).expect("YUV view must've valid dimensions!");
+        assert_eq!(img.to_rgb(), [vec![255, 0, 255, 0], vec![255, 0, 255, 0], vec![255, 0, 255, 0]]);
+    }
+
+    #[test]
+    fn yuv_compose_subsampled() {
+        let format = YuvFormat::by_name("YUV420P16", ColorMatrix::BT601, false).expect("Format name should be valid!");
+        let (mut luma, mut cb, mut cr) = (vec![0u8; 2 * 2 * 2], vec![0u8; 2], vec![0u8; 2]);
+        {
+            let mut img = YuvImageView::new(2, 2, format, [4, 2, 2], vec![&mut luma, &mut cb, &mut cr]).expect("YUV view must've valid dimensions!");
+            // White pixel at top-left only
+            img.compose([&[255, 0, 0, 0], &[255, 0, 0, 0], &[255, 0, 0, 0], &[255, 0, 0, 0]]);
+        }
+        let sample = |data: &[u8], index: usize| u16::from_ne_bytes([data[index * 2], data[index * 2 + 1]]);
+        assert_eq!((sample(&luma, 0), sample(&luma, 1)), (235 << 8, 0));
+        // Chroma covered by a quarter
+        assert_eq!((sample(&cb, 0), sample(&cr, 0)), (32 << 8, 32 << 8));
+        assert!(YuvImageView::new(2, 2, format, [2, 2, 2], vec![&mut [0u8; 8], &mut [0u8; 2], &mut [0u8; 2]]).is_err());
+    }
+}
\ No newline at end of file
@@ -1,12 +1,63 @@
 // Imports
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::Path as FilePath,
+    sync::Arc
+};
 use ssb_parser::{
     SsbRender,
-    objects::ssb_objects::EventTrigger
+    objects::{
+        ssb_objects::{EventTrigger,FontFace,FontStyle,TextureId},
+        event_objects::{EventObject,Point3D,Direction,WrapStyle,Alignment,Numpad,Target,MaskMode,Blend}
+    }
+};
+use meval::Expr;
+use puny2d::{
+    raster::{
+        image::{ColorType,ImageView},
+        mask::Mask
+    },
+    vector::{
+        point::Point,
+        path::{Path,FlatPath}
+    }
+};
+use crate::{
+    error::RenderingError,
+    state::{
+        style::StyleState,
+        transform::Camera,
+        animation::{EventTime,parse_easing,animation_progress},
+        index::EventIndex
+    },
+    raster::{
+        path::{shape_to_path,map_path,map_flat_path},
+        points::points_to_path,
+        stroke::stroke_path,
+        blur::blur_extent,
+        texture::Texture,
+        paint::Paint,
+        stencil::Stencil,
+        tile::{for_each_tile,fill_tile,place_mask,cut_mask},
+        cache::{MaskCache,MaskKey,CacheStats},
+        composite::fill_mask,
+        yuv::YuvImageView
+    },
+    text::{
+        font::FontCollection,
+        shape::GlyphCache,
+        layout::{TextRun,BlockOptions,layout_block}
+    }
 };
-use puny2d::raster::image::ImageView;
-use crate::error::RenderingError;
 
 
+// Default limits of coverage mask cache (count & memory in bytes)
+const CACHE_ENTRIES: usize = 4096;
+const CACHE_BYTES: usize = 256 << 20;
+// Subpixel steps of cached geometries
+const CACHE_QUANTIZATION: f32 = 64.0;
+
 /// Condition to trigger rendering on specific image.
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RenderTrigger<'a> {
@@ -14,40 +65,541 @@ pub enum RenderTrigger<'a> {
     Time(u32)
 }
 
+/// Rectangle of image pixels.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16
+}
+
+/// Bitmap at image position with premultiplied RGBA pixels (row by row, without padding).
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Overlay {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    pub data: Vec<u8>
+}
+
 /// Renderer for ssb data on images.
 #[derive(Debug, PartialEq, Clone)]
 pub struct SsbRenderer {
-    data: SsbRender
+    data: SsbRender,
+    events: EventIndex,
+    fonts: FontCollection,
+    textures: HashMap<TextureId, Result<Arc<Texture>, String>>,
+    easings: HashMap<String, Expr>,
+    balanced_wrapping: bool,
+    masks: MaskCache,
+    glyphs: GlyphCache
 }
 impl SsbRenderer {
     /// Consumes ssb data as rendering blueprint.
-    pub fn new(data: SsbRender) -> Self {
+    /// Textures which aren't valid images fail renderings using them.
+    /// Animation formulas which aren't valid expressions of 't' animate linearly.
+    pub fn new(mut data: SsbRender) -> Self {
         Self {
-            data
+            fonts: FontCollection::new(std::mem::take(&mut data.fonts)),
+            textures: std::mem::take(&mut data.textures).into_iter()
+                .map(|(id, data)| (id, Texture::decode(&data).map(Arc::new).map_err(|err| err.to_string())))
+                .collect(),
+            easings: data.events.iter()
+                .flat_map(|event| &event.objects)
+                .filter_map(|object| match object {
+                    EventObject::TagAnimate(animate) => animate.formula.as_ref(),
+                    _ => None
+                })
+                .filter_map(|formula| parse_easing(formula).ok().map(|easing| (formula.clone(), easing)))
+                .collect(),
+            events: EventIndex::new(&data.events),
+            data,
+            balanced_wrapping: false,
+            masks: MaskCache::new(CACHE_ENTRIES, CACHE_BYTES),
+            glyphs: GlyphCache::default()
         }
     }
+    /// Sets balanced wrapping: wrapped lines get similar lengths instead of filling up greedily.
+    pub fn set_balanced_wrapping(&mut self, balanced: bool) -> &mut Self {
+        self.balanced_wrapping = balanced;
+        self
+    }
+    /// Sets limits of cache which keeps rasterized geometries for following renderings: count & memory (in bytes).
+    /// Least recently used geometries get dropped first, zero entries disable caching.
+    pub fn set_cache_limits(&mut self, max_entries: usize, max_bytes: usize) -> &mut Self {
+        self.masks.set_limits(max_entries, max_bytes);
+        self
+    }
+    /// Usage of cache for rasterized geometries.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.masks.stats()
+    }
+    /// Loads fonts of local directory as fallback for missing embedded ones (which it owns and returns modified).
+    pub fn load_font_directory_owned<P>(mut self, directory: P) -> Result<Self, RenderingError>
+        where P: AsRef<FilePath> {
+        self.load_font_directory(directory)?;
+        Ok(self)
+    }
+    /// Loads fonts of local directory as fallback for missing embedded ones (which it borrows and returns as reference).
+    pub fn load_font_directory<P>(&mut self, directory: P) -> Result<&mut Self, RenderingError>
+        where P: AsRef<FilePath> {
+        self.fonts.load_directory(directory.as_ref())?;
+        Ok(self)
+    }
     /// Renders on image by ssb matching trigger.
-    pub fn render<'data>(&mut self, mut img: ImageView<'data>, trigger: RenderTrigger) -> Result<ImageView<'data>,RenderingError> {
-        // Find match of render and ssb trigger
-        for event in &self.data.events {
-            if match (&event.trigger, trigger) {
-                (EventTrigger::Id(event_id), RenderTrigger::Id(render_id)) => event_id == render_id,
-                (EventTrigger::Time((start_ms, end_ms)), RenderTrigger::Time(current_ms)) => (start_ms..end_ms).contains(&&current_ms),
-                _ => false
-            } {
-
-
-                // TODO: whole rendering process
-                for row in img.plane_rows_mut(0).expect("One plane should always exist!") {
-                    for sample in row {
-                        *sample = std::u8::MAX - *sample;
+    /// Renderer can be shared by threads, large images get drawn in parallel tiles.
+    pub fn render<'data>(&self, img: ImageView<'data>, trigger: RenderTrigger) -> Result<ImageView<'data>,RenderingError> {
+        self.render_region(img, trigger).map(|(img, _)| img)
+    }
+    /// Renders on image by ssb matching trigger and reports image rectangles which got touched (none if nothing was drawn).
+    pub fn render_region<'data>(&self, mut img: ImageView<'data>, trigger: RenderTrigger) -> Result<(ImageView<'data>, Vec<Rect>),RenderingError> {
+        let (width, height) = (img.width(), img.height());
+        let drawings = self.prepare(width, height, trigger)?;
+        self.draw(&mut img, &drawings);
+        // Return still valid image reference
+        Ok((img, drawing_bounds(&drawings, width, height)))
+    }
+    /// Renders by trigger into bitmaps for an image of given size (one per touched rectangle), so callers can blend them by themselves.
+    /// Only rectangles get allocated & drawn, without an image underneath blending modes mix with transparency.
+    pub fn render_overlays(&self, width: u16, height: u16, trigger: RenderTrigger) -> Result<Vec<Overlay>,RenderingError> {
+        let drawings = self.prepare(width, height, trigger)?;
+        drawing_bounds(&drawings, width, height).into_iter()
+            .map(|rect| {
+                let plane_size = rect.width as usize * rect.height as usize;
+                let (mut red, mut green, mut blue, mut alpha) = (vec![0u8; plane_size], vec![0u8; plane_size], vec![0u8; plane_size], vec![0u8; plane_size]);
+                self.draw(
+                    &mut ImageView::new(rect.width, rect.height, rect.width as u32, ColorType::R8G8B8A8, vec![&mut red, &mut green, &mut blue, &mut alpha])?,
+                    &drawings.iter().map(|drawing| crop_drawing(drawing, &rect)).collect::<Vec<_>>()
+                );
+                Ok(to_overlay([&red, &green, &blue, &alpha], rect))
+            })
+            .collect()
+    }
+    /// Checks whether any event matches trigger, so rendering could change an image at all.
+    pub fn is_active(&self, trigger: RenderTrigger) -> bool {
+        match trigger {
+            RenderTrigger::Id(id) => !self.events.by_id(id).is_empty(),
+            RenderTrigger::Time(current_ms) => !self.events.by_time(current_ms).is_empty()
+        }
+    }
+    /// Image rectangles which rendering by trigger would touch (without drawing).
+    /// Rectangles don't overlap but can include untouched pixels.
+    pub fn bounds(&self, width: u16, height: u16, trigger: RenderTrigger) -> Result<Vec<Rect>,RenderingError> {
+        Ok(drawing_bounds(&self.prepare(width, height, trigger)?, width, height))
+    }
+    // Rasterizes drawings on tiles, each with own mask
+    fn draw(&self, img: &mut ImageView, drawings: &[Drawing]) {
+        if drawings.is_empty() {
+            return;
+        }
+        let (width, height) = (img.width(), img.height());
+        for_each_tile(img, |mut tile, rows| {
+            let mut stencil = None;
+            for drawing in drawings {
+                draw_tile(&mut tile, &rows, (width, height), drawing, &self.masks, &mut stencil);
+            }
+        });
+    }
+    // Drawings of events matching render trigger (in script order) for image size
+    fn prepare(&self, width: u16, height: u16, trigger: RenderTrigger) -> Result<Vec<Drawing>,RenderingError> {
+        let mut drawings = vec![];
+        let indices = match trigger {
+            RenderTrigger::Id(id) => self.events.by_id(id).to_vec(),
+            RenderTrigger::Time(current_ms) => self.events.by_time(current_ms)
+        };
+        for event in indices.into_iter().map(|index| &self.data.events[index]) {
+            // Walk through event objects with running style
+            let time = match (&event.trigger, trigger) {
+                (EventTrigger::Time((start_ms, end_ms)), RenderTrigger::Time(current_ms)) => Some(EventTime {start: *start_ms, end: *end_ms, current: current_ms}),
+                _ => None
+            };
+            let elapsed = time.as_ref().map(EventTime::elapsed);
+            let mut style = StyleState::default();
+            let mut geometries = vec![];
+            for object in &event.objects {
+                match object {
+                    EventObject::GeometryShape(segments) => geometries.push((Geometry::Path(shape_to_path(segments)), style.geometry_style(elapsed))),
+                    EventObject::GeometryPoints(points) => geometries.push((Geometry::Dots(points_to_path(points, style.border.0, style.border.1, &style.cap)), style.geometry_style(elapsed))),
+                    EventObject::GeometryText(text) => geometries.push((Geometry::Text(text), style.geometry_style(elapsed))),
+                    EventObject::TagMaskClear => geometries.push((Geometry::MaskClear, style.clone())),
+                    EventObject::TagAnimate(animate) => style.animate(&animate.tags, animation_progress(
+                        animate,
+                        animate.formula.as_ref().and_then(|formula| self.easings.get(formula)),
+                        time.as_ref()
+                    )),
+                    tag => style.apply(tag)
+                }
+            }
+            // Lay out texts as one block in target frame
+            let frame = (
+                self.data.target_width.unwrap_or(width) as f32,
+                self.data.target_height.unwrap_or(height) as f32
+            );
+            let (text_paths, text_anchor) = layout_text(&self.fonts, &self.glyphs, &geometries, frame, self.balanced_wrapping)?;
+            let mut text_paths = text_paths.into_iter();
+            // Prepare geometries in order, projected by camera and scaled from target frame to image, clipped by event mask
+            let context = DrawContext {
+                camera: Camera {
+                    view: self.data.target_view.clone(),
+                    depth: self.data.target_depth as f32,
+                    center: Point {x: frame.0 * 0.5, y: frame.1 * 0.5}
+                },
+                scale: Point {x: width as f32 / frame.0, y: height as f32 / frame.1}
+            };
+            drawings.push(Drawing::MaskClear);
+            for (geometry, style) in &geometries {
+                match geometry {
+                    Geometry::Path(path) | Geometry::Dots(path) => {
+                        let anchor = style.anchor(Point3D {x: 0.0, y: 0.0, z: 0.0});
+                        drawings.push(prepare_path(path, matches!(geometry, Geometry::Path(_)), style, self.texture(style)?, &anchor, &context));
+                    }
+                    Geometry::Text(_) => if let Some(path) = text_paths.next() {
+                        drawings.push(prepare_path(&path, true, style, self.texture(style)?, &text_anchor, &context));
                     }
+                    Geometry::MaskClear => drawings.push(Drawing::MaskClear)
                 }
-
-
             }
         }
-        // Return still valid image reference
+        Ok(drawings)
+    }
+    /// Renders on planar YUV image by ssb matching trigger.
+    /// Drawings get composed as RGBA overlay to keep sample precision, unless any blends with image colors
+    /// which then get converted to RGB first (touched pixels lose precision).
+    pub fn render_yuv<'data>(&self, mut img: YuvImageView<'data>, trigger: RenderTrigger) -> Result<YuvImageView<'data>,RenderingError> {
+        let (width, height) = (img.width(), img.height());
+        let drawings = self.prepare(width, height, trigger)?;
+        if drawings.is_empty() {
+            return Ok(img);
+        }
+        let plane_size = width as usize * height as usize;
+        if drawings.iter().any(|drawing| matches!(drawing, Drawing::Paths {target: Target::Frame, blend: Some(_), ..})) {
+            // Draw on opaque image colors, changed pixels become overlay
+            let [mut red, mut green, mut blue] = img.to_rgb();
+            let original = [red.clone(), green.clone(), blue.clone()];
+            self.draw(
+                &mut ImageView::new(width, height, width as u32, ColorType::R8G8B8A8, vec![&mut red, &mut green, &mut blue, &mut vec![u8::MAX; plane_size]])?,
+                &drawings
+            );
+            let alpha = (0..plane_size)
+                .map(|index| if [&red, &green, &blue].iter().zip(&original).any(|(plane, original)| plane[index] != original[index]) {u8::MAX} else {0})
+                .collect::<Vec<_>>();
+            img.compose([&red, &green, &blue, &alpha]);
+        } else {
+            let (mut red, mut green, mut blue, mut alpha) = (vec![0u8; plane_size], vec![0u8; plane_size], vec![0u8; plane_size], vec![0u8; plane_size]);
+            self.draw(
+                &mut ImageView::new(width, height, width as u32, ColorType::R8G8B8A8, vec![&mut red, &mut green, &mut blue, &mut alpha])?,
+                &drawings
+            );
+            img.compose([&red, &green, &blue, &alpha]);
+        }
         Ok(img)
     }
+    // Texture of style (if any), missing or undecodable ones fail
+    fn texture(&self, style: &StyleState) -> Result<Option<&Arc<Texture>>,RenderingError> {
+        match &style.texture {
+            Some(texture_id) => match self.textures.get(texture_id) {
+                Some(Ok(texture)) => Ok(Some(texture)),
+                Some(Err(err)) => Err(RenderingError::new(&format!("Texture '{}' couldn't be decoded!\n{}", texture_id, err))),
+                None => Err(RenderingError::new(&format!("Texture '{}' not found!", texture_id)))
+            }
+            None => Ok(None)
+        }
+    }
+}
+
+// Helpers
+enum Geometry<'a> {
+    Path(Path),
+    // Points as dots, sized by border already
+    Dots(Path),
+    Text(&'a str),
+    MaskClear
+}
+struct DrawContext {
+    camera: Camera,
+    scale: Point
+}
+// Geometry in image space, ready for rasterization on any image part
+enum Drawing {
+    Paths {
+        // Border under fill, each with bounding & paint
+        paths: Vec<(FlatPath, (Point, Point), Paint)>,
+        // Both border & fill present, so fill gets cut out of border
+        bordered: bool,
+        blur: (f32, f32),
+        target: Target,
+        mask_mode: MaskMode,
+        blend: Option<Blend>
+    },
+    MaskClear
+}
+// Text paths relative to their anchor in frame space
+fn layout_text(fonts: &FontCollection, glyphs: &GlyphCache, geometries: &[(Geometry, StyleState)], frame: (f32, f32), balanced: bool) -> Result<(Vec<Path>, Point3D), RenderingError> {
+    // Text runs with fonts by style, texts without any font get skipped
+    let (mut runs, mut resolved) = (vec![], vec![]);
+    for (geometry, style) in geometries {
+        if let Geometry::Text(text) = geometry {
+            let font_face = FontFace {
+                family: style.font.clone(),
+                style: match (style.bold, style.italic) {
+                    (false, false) => FontStyle::Regular,
+                    (true, false) => FontStyle::Bold,
+                    (false, true) => FontStyle::Italic,
+                    (true, true) => FontStyle::BoldItalic
+                }
+            };
+            // Missing styles get synthesized from closest one of family, missing families replaced by any available one
+            let (face, synthesis) = match fonts.get_closest(&font_face).or_else(|| fonts.get_replacement(&font_face.style)) {
+                Some(font) => font,
+                None => {
+                    resolved.push(false);
+                    continue;
+                }
+            };
+            resolved.push(true);
+            runs.push(TextRun {
+                face,
+                synthesis,
+                text,
+                size: style.size,
+                // Horizontal spacing is between letters of horizontal lines but between vertical lines
+                letter_spacing: match style.direction {
+                    Direction::LeftToRight | Direction::RightToLeft => style.space.0,
+                    Direction::TopToBottom | Direction::BottomToTop => style.space.1
+                },
+                underline: style.underline,
+                strikeout: style.strikeout
+            });
+        }
+    }
+    // Block options by style of first text
+    let block_style = match geometries.iter().find(|(geometry, _)| matches!(geometry, Geometry::Text(_))) {
+        Some((_, style)) => style,
+        None => return Ok((vec![], Point3D {x: 0.0, y: 0.0, z: 0.0}))
+    };
+    let (top, right, bottom, left) = block_style.margin;
+    let vertical = matches!(block_style.direction, Direction::TopToBottom | Direction::BottomToTop);
+    let alignment = match &block_style.alignment {
+        Alignment::Numpad(numpad) => numpad_alignment(numpad),
+        Alignment::Offset(_) => (0.0, 0.0)
+    };
+    let block = layout_block(&runs, &BlockOptions {
+        direction: block_style.direction.clone(),
+        wrap_style: block_style.wrap_style.clone(),
+        max_line_length: match (&block_style.wrap_style, &block_style.direction) {
+            (WrapStyle::NoWrap, _) => None,
+            (_, Direction::LeftToRight) | (_, Direction::RightToLeft) => Some(frame.0 - left - right),
+            (_, Direction::TopToBottom) | (_, Direction::BottomToTop) => Some(frame.1 - top - bottom)
+        },
+        balanced,
+        line_alignment: if vertical {alignment.1} else {alignment.0},
+        line_spacing: if vertical {block_style.space.0} else {block_style.space.1}
+    }, glyphs);
+    // Anchor by position or frame inside margins, block attached by alignment (numpad: to block box, offset: to block origin)
+    let block_left = if vertical {-block.width} else {0.0};
+    let (anchor, block_anchor) = match &block_style.alignment {
+        Alignment::Numpad(_) => (
+            block_style.anchor(
+                Point3D {x: left + (frame.0 - left - right) * alignment.0, y: top + (frame.1 - top - bottom) * alignment.1, z: 0.0}
+            ),
+            Point {x: block_left + block.width * alignment.0, y: block.height * alignment.1}
+        ),
+        Alignment::Offset(offset) => (
+            block_style.anchor(Point3D {x: left, y: top, z: 0.0}),
+            Point {x: offset.x, y: offset.y}
+        )
+    };
+    let mut paths = block.paths.iter().peekable();
+    Ok((
+        resolved.into_iter()
+            .map(|resolved| match paths.next_if(|_| resolved) {
+                Some(path) => map_path(path, |point| point - block_anchor),
+                None => Path::default()
+            })
+            .collect(),
+        anchor
+    ))
+}
+// Horizontal & vertical alignment factors (0 = left/top, 0.5 = center/middle, 1 = right/bottom)
+fn numpad_alignment(numpad: &Numpad) -> (f32, f32) {
+    match numpad {
+        Numpad::TopLeft => (0.0, 0.0),
+        Numpad::TopCenter => (0.5, 0.0),
+        Numpad::TopRight => (1.0, 0.0),
+        Numpad::MiddleLeft => (0.0, 0.5),
+        Numpad::MiddleCenter => (0.5, 0.5),
+        Numpad::MiddleRight => (1.0, 0.5),
+        Numpad::BottomLeft => (0.0, 1.0),
+        Numpad::BottomCenter => (0.5, 1.0),
+        Numpad::BottomRight => (1.0, 1.0)
+    }
+}
+// Path gets transformed around anchor before projection, curves get flattened first to stay correct in perspective
+// Border lies under fill (if bordered), texture covers fill only
+fn prepare_path(path: &Path, bordered: bool, style: &StyleState, texture: Option<&Arc<Texture>>, anchor: &Point3D, context: &DrawContext) -> Drawing {
+    let (camera, scale) = (&context.camera, context.scale);
+    let path = FlatPath::from(path.clone());
+    let to_image = |path: &FlatPath| map_flat_path(path, |point| {
+        let point = style.transform.transform(point);
+        let point = camera.project(&Point3D {x: point.x + anchor.x, y: point.y + anchor.y, z: point.z + anchor.z});
+        Point {x: point.x * scale.x, y: point.y * scale.y}
+    });
+    let mut paths = vec![];
+    let mut has_border = false;
+    if bordered {
+        let border_path = to_image(&stroke_path(&path, style.border.0, style.border.1, &style.join, &style.cap));
+        if let Some(area) = border_path.bounding() {
+            paths.push((border_path, area, Paint::new(&style.border_color, &style.border_alpha, area)));
+            has_border = true;
+        }
+    }
+    let path = to_image(&path);
+    if let Some(area) = path.bounding() {
+        let paint = Paint::new(&style.color, &style.alpha, area);
+        paths.push((path, area, match texture {
+            Some(texture) => {
+                let (start, end, wrap) = style.texture_fill.clone();
+                paint.with_texture(texture.clone(), start, end, wrap)
+            }
+            None => paint
+        }));
+    }
+    Drawing::Paths {
+        bordered: has_border && paths.len() == 2,
+        paths,
+        blur: (style.blur.0 * scale.x, style.blur.1 * scale.y),
+        target: style.target.clone(),
+        mask_mode: style.mask_mode.clone(),
+        blend: style.blend.clone()
+    }
+}
+// Blur softens paths, drawing on frame gets clipped by mask (if anything was drawn into it yet)
+// Rasterized paths get reused from cache
+fn draw_tile(tile: &mut ImageView, rows: &Range<u16>, (width, height): (u16, u16), drawing: &Drawing, masks: &MaskCache, stencil: &mut Option<Stencil>) {
+    match drawing {
+        Drawing::Paths {paths, bordered, blur, target, mask_mode, blend} => {
+            let extent = blur_extent(blur.1) as f32;
+            let offset = Point {x: 0.0, y: -(rows.start as f32)};
+            // Mask clips all tiles, even those it doesn't reach or lying beside image
+            if matches!(target, Target::Mask) {
+                stencil.get_or_insert_with(|| Stencil::new(tile.width(), tile.height()));
+            }
+            // Rasterize paths (except those beside tile), border shows through translucent fill only beside it
+            let mut path_masks = paths.iter()
+                .map(|(path, area, _)| if area.1.y + extent < rows.start as f32 || area.0.y - extent > rows.end as f32 {
+                    None
+                } else {
+                    tile_mask(masks, path, area, *blur, (width, height), rows)
+                })
+                .collect::<Vec<_>>();
+            if let (true, [Some(border_mask), Some(fill_mask)]) = (*bordered, path_masks.as_mut_slice()) {
+                cut_mask(border_mask, fill_mask);
+            }
+            for ((_, _, paint), mask) in paths.iter().zip(path_masks) {
+                if let Some(mask) = mask {
+                    let paint = paint.translated(offset);
+                    match target {
+                        Target::Frame => match stencil {
+                            Some(stencil) => fill_mask(tile, &stencil.clip(mask, matches!(mask_mode, MaskMode::Invert)), &paint, blend.as_ref()),
+                            None => fill_mask(tile, &mask, &paint, blend.as_ref())
+                        },
+                        Target::Mask => stencil.get_or_insert_with(|| Stencil::new(tile.width(), tile.height())).draw(&mask, &paint)
+                    }
+                }
+            }
+        }
+        Drawing::MaskClear => *stencil = None
+    }
+}
+// Coverage of path in tile rows (relative to tile), reused from cache
+// Path gets rasterized relative to its integer pixel origin (quantized), so translated copies share masks
+fn tile_mask(masks: &MaskCache, path: &FlatPath, area: &(Point, Point), blur: (f32, f32), (width, height): (u16, u16), rows: &Range<u16>) -> Option<Mask> {
+    let extent = (blur_extent(blur.0) as f32, blur_extent(blur.1) as f32);
+    let origin = ((area.0.x - extent.0).floor(), (area.0.y - extent.1).floor());
+    let size = ((area.1.x + extent.0).ceil() - origin.0, (area.1.y + extent.1).ceil() - origin.1);
+    // Geometries beyond frame limits get rasterized in image space
+    if size.0 > u16::MAX as f32 || size.1 > u16::MAX as f32 || origin.0.abs() > i32::MAX as f32 || origin.1.abs() > i32::MAX as f32 {
+        let mask = masks.get_or_insert_with(MaskKey::new(path, blur, width, height), || fill_tile(path, width, height, &(0..height), blur))?;
+        return place_mask(&mask, (0, -(rows.start as i32)), width, rows.end - rows.start);
+    }
+    let (origin, size) = ((origin.0 as i32, origin.1 as i32), (size.0 as u16, size.1 as u16));
+    if origin.1 + size.1 as i32 <= rows.start as i32 || origin.1 >= rows.end as i32 {
+        return None;
+    }
+    // Whole frame gets cached, tile cuts its rows out
+    let quantize = |value: f32| (value * CACHE_QUANTIZATION).round() / CACHE_QUANTIZATION;
+    let frame_path = map_flat_path(path, |point| Point {x: quantize(point.x - origin.0 as f32), y: quantize(point.y - origin.1 as f32)});
+    let mask = masks.get_or_insert_with(
+        MaskKey::new(&frame_path, blur, size.0, size.1),
+        || fill_tile(&frame_path, size.0, size.1, &(0..size.1), blur)
+    )?;
+    place_mask(&mask, (origin.0, origin.1 - rows.start as i32), width, rows.end - rows.start)
+}
+// Image rectangles of drawings on frame (with blur spread), overlapping ones merged
+fn drawing_bounds(drawings: &[Drawing], width: u16, height: u16) -> Vec<Rect> {
+    let mut rects: Vec<(u16, u16, u16, u16)> = vec![];
+    for drawing in drawings {
+        if let Drawing::Paths {paths, blur, target: Target::Frame, ..} = drawing {
+            let extent = (blur_extent(blur.0) as f32, blur_extent(blur.1) as f32);
+            for (_, area, _) in paths {
+                let (left, top) = ((area.0.x - extent.0).floor().max(0.0), (area.0.y - extent.1).floor().max(0.0));
+                let (right, bottom) = ((area.1.x + extent.0).ceil().min(width as f32), (area.1.y + extent.1).ceil().min(height as f32));
+                if right <= left || bottom <= top {
+                    continue;
+                }
+                // Grow rectangle by overlapping ones until it's free
+                let mut rect = (left as u16, top as u16, right as u16, bottom as u16);
+                while let Some(position) = rects.iter().position(|other| other.0 < rect.2 && rect.0 < other.2 && other.1 < rect.3 && rect.1 < other.3) {
+                    let other = rects.swap_remove(position);
+                    rect = (rect.0.min(other.0), rect.1.min(other.1), rect.2.max(other.2), rect.3.max(other.3));
+                }
+                rects.push(rect);
+            }
+        }
+    }
+    rects.into_iter().map(|(left, top, right, bottom)| Rect {x: left, y: top, width: right - left, height: bottom - top}).collect()
+}
+// Whether path area (grown by blur) overlaps image region (left, top, right, bottom)
+fn overlaps(area: &(Point, Point), blur: (f32, f32), region: (f32, f32, f32, f32)) -> bool {
+    let extent = (blur_extent(blur.0) as f32, blur_extent(blur.1) as f32);
+    area.1.x + extent.0 >= region.0 && area.0.x - extent.0 < region.2 && area.1.y + extent.1 >= region.1 && area.0.y - extent.1 < region.3
+}
+// Drawing moved into image rectangle, paths beside it dropped
+fn crop_drawing(drawing: &Drawing, rect: &Rect) -> Drawing {
+    match drawing {
+        Drawing::Paths {paths, bordered, blur, target, mask_mode, blend} => {
+            let region = (rect.x as f32, rect.y as f32, (rect.x + rect.width) as f32, (rect.y + rect.height) as f32);
+            let offset = Point {x: -region.0, y: -region.1};
+            Drawing::Paths {
+                paths: paths.iter()
+                    .filter(|(_, area, _)| overlaps(area, *blur, region))
+                    .map(|(path, area, paint)| (map_flat_path(path, |point| point + offset), (area.0 + offset, area.1 + offset), paint.translated(offset)))
+                    .collect(),
+                bordered: *bordered,
+                blur: *blur,
+                target: target.clone(),
+                mask_mode: mask_mode.clone(),
+                blend: blend.clone()
+            }
+        }
+        Drawing::MaskClear => Drawing::MaskClear
+    }
+}
+// Straight RGBA planes of image rectangle as premultiplied overlay
+fn to_overlay(planes: [&[u8];4], rect: Rect) -> Overlay {
+    Overlay {
+        data: (0..rect.width as usize * rect.height as usize)
+            .flat_map(|index| {
+                let alpha = planes[3][index];
+                let premultiply = |sample: u8| ((sample as u16 * alpha as u16 + 127) / u8::MAX as u16) as u8;
+                [premultiply(planes[0][index]), premultiply(planes[1][index]), premultiply(planes[2][index]), alpha]
+            })
+            .collect(),
+        x: rect.x,
+        y: rect.y,
+        width: rect.width,
+        height: rect.height
+    }
 }
\ No newline at end of file
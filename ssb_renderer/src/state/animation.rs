@@ -0,0 +1,88 @@
+// Imports
+use ssb_parser::objects::event_objects::Animate;
+use meval::{Expr,Context};
+use crate::error::RenderingError;
+
+
+/// Time of event (start & end) and of rendering (in milliseconds).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EventTime {
+    pub start: u32,
+    pub end: u32,
+    pub current: u32
+}
+impl EventTime {
+    /// Time since event start.
+    pub fn elapsed(&self) -> i64 {
+        self.current as i64 - self.start as i64
+    }
+}
+
+/// Parses easing formula (with progress as variable 't') for animations.
+pub fn parse_easing(formula: &str) -> Result<Expr, RenderingError> {
+    formula.parse::<Expr>()
+        .and_then(|expr| expr.clone().bind("t").map(|_| expr))
+        .map_err(|err| RenderingError::new_with_source(&format!("Invalid animation formula '{}'!", formula), err))
+}
+
+/// Animation progress (0 = start values, 1 = target values) by time range and parsed easing formula.
+/// Range times are relative to event start, negative ones relative to event end. Without range the whole event gets animated.
+/// Without event time (triggered by id) animation counts as finished.
+pub fn animation_progress(animate: &Animate, easing: Option<&Expr>, time: Option<&EventTime>) -> f32 {
+    let progress = match time {
+        Some(time) => {
+            let duration = time.end as i64 - time.start as i64;
+            let to_event_time = |range_time: i32| if range_time < 0 {duration + range_time as i64} else {range_time as i64};
+            let (start, end) = animate.time.map_or((0, duration), |(start, end)| (to_event_time(start), to_event_time(end)));
+            let current = time.elapsed();
+            if end > start {
+                ((current - start) as f64 / (end - start) as f64).clamp(0.0, 1.0)
+            } else if current >= start {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        None => 1.0
+    };
+    (match easing.and_then(|easing| easing.eval_with_context((("t", progress), Context::new())).ok()) {
+        Some(eased) if eased.is_finite() => eased,
+        _ => progress
+    }) as f32
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{parse_easing,animation_progress,Animate,EventTime};
+
+    fn animate(time: Option<(i32, i32)>) -> Animate {
+        Animate {
+            time,
+            formula: None,
+            tags: vec![]
+        }
+    }
+
+    #[test]
+    fn progress_by_time() {
+        let time = EventTime {start: 1000, end: 3000, current: 1500};
+        assert_eq!(animation_progress(&animate(None), None, Some(&time)), 0.25);
+        assert_eq!(animation_progress(&animate(Some((500, 1500))), None, Some(&time)), 0.0);
+        assert_eq!(animation_progress(&animate(Some((0, -1000))), None, Some(&time)), 0.5);
+        assert_eq!(animation_progress(&animate(Some((200, 200))), None, Some(&time)), 1.0);
+        assert_eq!(animation_progress(&animate(Some((0, 100))), None, None), 1.0);
+    }
+
+    #[test]
+    fn progress_by_formula() {
+        let time = EventTime {start: 0, end: 1000, current: 500};
+        let progress = |formula: &str| parse_easing(formula).map(|easing| animation_progress(&animate(None), Some(&easing), Some(&time))).ok();
+        assert_eq!(progress("t^2"), Some(0.25));
+        assert_eq!(progress("1 - t * 3"), Some(-0.5));
+        assert_eq!(progress("1 / (t - 0.5)"), Some(0.5));
+        assert!(parse_easing("t +").is_err());
+        assert!(parse_easing("x").is_err());
+    }
+}
\ No newline at end of file
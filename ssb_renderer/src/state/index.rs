@@ -0,0 +1,133 @@
+// Imports
+use std::collections::HashMap;
+use ssb_parser::objects::ssb_objects::{EventRender,EventTrigger};
+
+
+/// Positions of events by trigger: ids in a map, time ranges in a centered interval tree.
+/// Lookups cost logarithmic time plus matches instead of a scan over all events.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct EventIndex {
+    ids: HashMap<String, Vec<usize>>,
+    times: Option<Box<IntervalNode>>
+}
+impl EventIndex {
+    /// Indexes events by their triggers.
+    pub fn new(events: &[EventRender]) -> Self {
+        let mut ids = HashMap::<String, Vec<usize>>::new();
+        let mut intervals = vec![];
+        for (index, event) in events.iter().enumerate() {
+            match &event.trigger {
+                EventTrigger::Id(id) => ids.entry(id.clone()).or_default().push(index),
+                EventTrigger::Time((start, end)) => if start < end {
+                    intervals.push(Interval {start: *start, end: *end, index});
+                }
+            }
+        }
+        Self {
+            ids,
+            times: IntervalNode::build(intervals)
+        }
+    }
+    /// Positions of events with id (in event order).
+    pub fn by_id(&self, id: &str) -> &[usize] {
+        self.ids.get(id).map_or(&[], Vec::as_slice)
+    }
+    /// Positions of events running at time (in event order), ranges include start but exclude end.
+    pub fn by_time(&self, time: u32) -> Vec<usize> {
+        let mut indices = vec![];
+        let mut node = self.times.as_deref();
+        while let Some(current) = node {
+            if time < current.center {
+                indices.extend(current.by_start.iter().take_while(|interval| interval.start <= time).map(|interval| interval.index));
+                node = current.left.as_deref();
+            } else {
+                indices.extend(current.by_end.iter().take_while(|interval| interval.end > time).map(|interval| interval.index));
+                node = current.right.as_deref();
+            }
+        }
+        indices.sort_unstable();
+        indices
+    }
+}
+
+// Time range of event
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Interval {
+    start: u32,
+    end: u32,
+    index: usize
+}
+
+// Intervals around center (sorted by start ascending & by end descending), others in subtrees beside
+#[derive(Debug, PartialEq, Clone)]
+struct IntervalNode {
+    center: u32,
+    by_start: Vec<Interval>,
+    by_end: Vec<Interval>,
+    left: Option<Box<IntervalNode>>,
+    right: Option<Box<IntervalNode>>
+}
+impl IntervalNode {
+    fn build(mut intervals: Vec<Interval>) -> Option<Box<Self>> {
+        if intervals.is_empty() {
+            return None;
+        }
+        // Median of range starts splits intervals evenly and is contained by at least one
+        let mut starts = intervals.iter().map(|interval| interval.start).collect::<Vec<_>>();
+        let middle = starts.len() / 2;
+        let center = *starts.select_nth_unstable(middle).1;
+        let (mut left, mut right) = (vec![], vec![]);
+        intervals.retain(|interval| if interval.end <= center {
+            left.push(*interval);
+            false
+        } else if interval.start > center {
+            right.push(*interval);
+            false
+        } else {
+            true
+        });
+        let mut by_end = intervals.clone();
+        intervals.sort_unstable_by_key(|interval| interval.start);
+        by_end.sort_unstable_by_key(|interval| std::cmp::Reverse(interval.end));
+        Some(Box::new(Self {
+            center,
+            by_start: intervals,
+            by_end,
+            left: Self::build(left),
+            right: Self::build(right)
+        }))
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{EventIndex,EventRender,EventTrigger};
+
+    fn events(triggers: Vec<EventTrigger>) -> Vec<EventRender> {
+        triggers.into_iter().map(|trigger| EventRender {trigger, objects: vec![]}).collect()
+    }
+
+    #[test]
+    fn index_by_id() {
+        let index = EventIndex::new(&events(vec![EventTrigger::Id("a".to_owned()), EventTrigger::Time((0, 10)), EventTrigger::Id("a".to_owned())]));
+        assert_eq!(index.by_id("a"), &[0, 2]);
+        assert!(index.by_id("b").is_empty());
+    }
+
+    #[test]
+    fn index_by_time() {
+        let ranges = [(0, 10), (5, 15), (10, 20), (3, 4), (100, 200), (0, 1000), (7, 7), (150, 151)];
+        let index = EventIndex::new(&events(ranges.iter().map(|range| EventTrigger::Time(*range)).collect()));
+        // Equal to linear scan
+        for time in 0..1100 {
+            assert_eq!(
+                index.by_time(time),
+                ranges.iter().enumerate().filter(|(_, (start, end))| (start..end).contains(&&time)).map(|(position, _)| position).collect::<Vec<_>>(),
+                "Time: {}", time
+            );
+        }
+        assert!(EventIndex::default().by_time(0).is_empty());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+// Running style of event objects.
+pub mod style;
+// 3d transformations & camera projection.
+pub mod transform;
+// Animation progress by time & easing.
+pub mod animation;
+// Event lookup by trigger.
+pub mod index;
\ No newline at end of file
@@ -0,0 +1,339 @@
+// Imports
+use ssb_parser::objects::event_objects::{EventObject,Point3D,Coordinate,Rotate,Scale,Translate,Shear,Border,Blur,Blend,Space,Target,MaskMode,Join,Cap,TextureWrapping,Color,Alpha,Rgb,Direction,Alignment,Numpad,Margin,WrapStyle};
+use puny2d::vector::point::Point;
+use super::transform::TransformStack;
+
+
+/// Style state, modified by tags while walking through event objects.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StyleState {
+    pub font: String,
+    pub size: f32,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikeout: bool,
+    pub alignment: Alignment,
+    pub margin: (Coordinate, Coordinate, Coordinate, Coordinate),
+    pub wrap_style: WrapStyle,
+    pub direction: Direction,
+    pub position: Option<Point3D>,
+    pub position_animation: Option<(Point3D, f32)>,
+    pub space: (Coordinate, Coordinate),
+    pub transform: TransformStack,
+    pub border: (Coordinate, Coordinate),
+    pub join: Join,
+    pub cap: Cap,
+    pub texture: Option<String>,
+    pub texture_fill: (Point, Point, TextureWrapping),
+    pub color: Color,
+    pub border_color: Color,
+    pub alpha: Alpha,
+    pub border_alpha: Alpha,
+    pub blur: (Coordinate, Coordinate),
+    pub blend: Option<Blend>,
+    pub target: Target,
+    pub mask_mode: MaskMode,
+    pub karaoke: Option<i64>,
+    pub karaoke_position: i64,
+    pub karaoke_color: Rgb
+}
+impl Default for StyleState {
+    fn default() -> Self {
+        Self {
+            font: "Arial".to_owned(),
+            size: 30.0,
+            bold: false,
+            italic: false,
+            underline: false,
+            strikeout: false,
+            alignment: Alignment::Numpad(Numpad::BottomCenter),
+            margin: (0.0, 0.0, 0.0, 0.0),
+            wrap_style: WrapStyle::Space,
+            direction: Direction::LeftToRight,
+            position: None,
+            position_animation: None,
+            space: (0.0, 0.0),
+            transform: TransformStack::default(),
+            border: (0.0, 0.0),
+            join: Join::Round,
+            cap: Cap::Round,
+            texture: None,
+            texture_fill: (Point {x: 0.0, y: 0.0}, Point {x: 1.0, y: 1.0}, TextureWrapping::Pad),
+            color: Color::Mono([255, 255, 255]),
+            border_color: Color::Mono([0, 0, 0]),
+            alpha: Alpha::Mono(255),
+            border_alpha: Alpha::Mono(255),
+            blur: (0.0, 0.0),
+            blend: None,
+            target: Target::Frame,
+            mask_mode: MaskMode::Normal,
+            karaoke: None,
+            karaoke_position: 0,
+            karaoke_color: [255, 255, 0]
+        }
+    }
+}
+impl StyleState {
+    /// Applies tag on style. Geometries and tags without style influence are ignored.
+    pub fn apply(&mut self, tag: &EventObject) {
+        match tag {
+            EventObject::TagFont(font) => self.font = font.to_owned(),
+            EventObject::TagSize(size) => self.size = *size,
+            EventObject::TagBold(bold) => self.bold = *bold,
+            EventObject::TagItalic(italic) => self.italic = *italic,
+            EventObject::TagUnderline(underline) => self.underline = *underline,
+            EventObject::TagStrikeout(strikeout) => self.strikeout = *strikeout,
+            EventObject::TagAlignment(alignment) => self.alignment = alignment.clone(),
+            EventObject::TagMargin(margin) => self.margin = match margin {
+                Margin::All(top, right, bottom, left) => (*top, *right, *bottom, *left),
+                Margin::Top(top) => (*top, self.margin.1, self.margin.2, self.margin.3),
+                Margin::Right(right) => (self.margin.0, *right, self.margin.2, self.margin.3),
+                Margin::Bottom(bottom) => (self.margin.0, self.margin.1, *bottom, self.margin.3),
+                Margin::Left(left) => (self.margin.0, self.margin.1, self.margin.2, *left)
+            },
+            EventObject::TagWrapStyle(wrap_style) => self.wrap_style = wrap_style.clone(),
+            EventObject::TagDirection(direction) => self.direction = direction.clone(),
+            EventObject::TagPosition(position) => {
+                self.position = Some(position.clone());
+                self.position_animation = None;
+            }
+            EventObject::TagSpace(space) => self.space = match space {
+                Space::All(horizontal, vertical) => (*horizontal, *vertical),
+                Space::Horizontal(horizontal) => (*horizontal, self.space.1),
+                Space::Vertical(vertical) => (self.space.0, *vertical)
+            },
+            EventObject::TagRotate(rotate) => self.transform.rotate(rotate),
+            EventObject::TagScale(scale) => self.transform.scale(scale),
+            EventObject::TagTranslate(translate) => self.transform.translate(translate),
+            EventObject::TagShear(shear) => self.transform.shear(shear),
+            EventObject::TagMatrix(matrix) => self.transform.push(**matrix),
+            EventObject::TagReset => self.transform.reset(),
+            EventObject::TagBorder(border) => self.border = match border {
+                Border::All(horizontal, vertical) => (*horizontal, *vertical),
+                Border::Horizontal(horizontal) => (*horizontal, self.border.1),
+                Border::Vertical(vertical) => (self.border.0, *vertical)
+            },
+            EventObject::TagJoin(join) => self.join = join.clone(),
+            EventObject::TagCap(cap) => self.cap = cap.clone(),
+            EventObject::TagTexture(texture) => self.texture = if texture.is_empty() {None} else {Some(texture.to_owned())},
+            EventObject::TagTexFill{x0, y0, x1, y1, wrap} => self.texture_fill = (Point {x: *x0, y: *y0}, Point {x: *x1, y: *y1}, wrap.clone()),
+            EventObject::TagColor(color) => self.color = color.clone(),
+            EventObject::TagBorderColor(color) => self.border_color = color.clone(),
+            EventObject::TagAlpha(alpha) => self.alpha = alpha.clone(),
+            EventObject::TagBorderAlpha(alpha) => self.border_alpha = alpha.clone(),
+            EventObject::TagBlur(blur) => self.blur = match blur {
+                Blur::All(horizontal, vertical) => (*horizontal, *vertical),
+                Blur::Horizontal(horizontal) => (*horizontal, self.blur.1),
+                Blur::Vertical(vertical) => (self.blur.0, *vertical)
+            },
+            EventObject::TagBlend(blend) => self.blend = Some(blend.clone()),
+            EventObject::TagTarget(target) => self.target = target.clone(),
+            EventObject::TagMaskMode(mask_mode) => self.mask_mode = mask_mode.clone(),
+            EventObject::TagKaraoke(duration) => {
+                self.karaoke = Some(self.karaoke_position);
+                self.karaoke_position += *duration as i64;
+            }
+            EventObject::TagKaraokeSet(position) => self.karaoke_position = *position as i64,
+            EventObject::TagKaraokeColor(color) => self.karaoke_color = *color,
+            _ => {}
+        }
+    }
+    /// Style for geometry at time since event start: karaoke syllables which started get filled by karaoke color.
+    /// Without time (triggered by id) all syllables count as started.
+    pub fn geometry_style(&self, elapsed: Option<i64>) -> Self {
+        let mut style = self.clone();
+        if matches!(self.karaoke, Some(start) if !matches!(elapsed, Some(elapsed) if elapsed < start)) {
+            style.color = Color::Mono(self.karaoke_color);
+        }
+        style
+    }
+    /// Position or default anchor, to which a position animated without start position gets interpolated.
+    pub fn anchor(&self, default: Point3D) -> Point3D {
+        match (&self.position, &self.position_animation) {
+            (Some(position), _) => position.clone(),
+            (None, Some((target, progress))) => lerp_point(&default, target, *progress),
+            (None, None) => default
+        }
+    }
+    /// Applies tags partially by animation progress (0 = unchanged, 1 = fully applied).
+    /// Numeric, color, alpha and transform values get interpolated, others switch when animation finished.
+    pub fn animate(&mut self, tags: &[EventObject], progress: f32) {
+        let lerp = |from: Coordinate, to: Coordinate| from + (to - from) * progress;
+        // Transformations get pushed with scaled parameters
+        let mut target = self.clone();
+        for tag in tags {
+            match tag {
+                EventObject::TagRotate(rotate) => target.transform.rotate(&match rotate {
+                    Rotate::X(angle) => Rotate::X(angle * progress),
+                    Rotate::Y(angle) => Rotate::Y(angle * progress),
+                    Rotate::Z(angle) => Rotate::Z(angle * progress)
+                }),
+                EventObject::TagScale(scale) => target.transform.scale(&match scale {
+                    Scale::All(x, y, z) => Scale::All(lerp(1.0, *x), lerp(1.0, *y), lerp(1.0, *z)),
+                    Scale::X(x) => Scale::X(lerp(1.0, *x)),
+                    Scale::Y(y) => Scale::Y(lerp(1.0, *y)),
+                    Scale::Z(z) => Scale::Z(lerp(1.0, *z))
+                }),
+                EventObject::TagTranslate(translate) => target.transform.translate(&match translate {
+                    Translate::All(x, y, z) => Translate::All(x * progress, y * progress, z * progress),
+                    Translate::X(x) => Translate::X(x * progress),
+                    Translate::Y(y) => Translate::Y(y * progress),
+                    Translate::Z(z) => Translate::Z(z * progress)
+                }),
+                EventObject::TagShear(shear) => target.transform.shear(&match shear {
+                    Shear::All(x, y) => Shear::All(x * progress, y * progress),
+                    Shear::X(x) => Shear::X(x * progress),
+                    Shear::Y(y) => Shear::Y(y * progress)
+                }),
+                EventObject::TagMatrix(matrix) => {
+                    let mut partial = [0.0; 16];
+                    for (index, (partial, value)) in partial.iter_mut().zip(matrix.iter()).enumerate() {
+                        *partial = lerp(if index % 5 == 0 {1.0} else {0.0}, *value);
+                    }
+                    target.transform.push(partial);
+                }
+                EventObject::TagReset => if progress >= 1.0 {
+                    target.transform.reset();
+                },
+                tag => target.apply(tag)
+            }
+        }
+        // Interpolate values between current & target state
+        let finished = progress >= 1.0;
+        let mut result = if finished {target.clone()} else {self.clone()};
+        result.transform = target.transform;
+        result.size = lerp(self.size, target.size);
+        result.margin = (lerp(self.margin.0, target.margin.0), lerp(self.margin.1, target.margin.1), lerp(self.margin.2, target.margin.2), lerp(self.margin.3, target.margin.3));
+        match (&self.position, &target.position) {
+            (Some(position), Some(target_position)) => result.position = Some(lerp_point(position, target_position, progress)),
+            // Start position unknown yet (default anchor depends on geometry)
+            (None, Some(target_position)) if !finished => result.position_animation = Some((target_position.clone(), progress)),
+            _ => {}
+        }
+        result.space = (lerp(self.space.0, target.space.0), lerp(self.space.1, target.space.1));
+        result.border = (lerp(self.border.0, target.border.0), lerp(self.border.1, target.border.1));
+        result.texture_fill.0 = Point {x: lerp(self.texture_fill.0.x, target.texture_fill.0.x), y: lerp(self.texture_fill.0.y, target.texture_fill.0.y)};
+        result.texture_fill.1 = Point {x: lerp(self.texture_fill.1.x, target.texture_fill.1.x), y: lerp(self.texture_fill.1.y, target.texture_fill.1.y)};
+        result.color = lerp_color(&self.color, &target.color, progress).unwrap_or(result.color);
+        result.border_color = lerp_color(&self.border_color, &target.border_color, progress).unwrap_or(result.border_color);
+        result.alpha = lerp_alpha(&self.alpha, &target.alpha, progress).unwrap_or(result.alpha);
+        result.border_alpha = lerp_alpha(&self.border_alpha, &target.border_alpha, progress).unwrap_or(result.border_alpha);
+        result.blur = (lerp(self.blur.0, target.blur.0), lerp(self.blur.1, target.blur.1));
+        *self = result;
+    }
+}
+
+// Interpolation of positions
+fn lerp_point(from: &Point3D, to: &Point3D, progress: f32) -> Point3D {
+    Point3D {x: from.x + (to.x - from.x) * progress, y: from.y + (to.y - from.y) * progress, z: from.z + (to.z - from.z) * progress}
+}
+// Interpolations of equal color & alpha variants
+fn lerp_byte(from: u8, to: u8, progress: f32) -> u8 {
+    (from as f32 + (to as f32 - from as f32) * progress).round().clamp(0.0, u8::MAX as f32) as u8
+}
+fn lerp_rgb(from: Rgb, to: Rgb, progress: f32) -> Rgb {
+    [lerp_byte(from[0], to[0], progress), lerp_byte(from[1], to[1], progress), lerp_byte(from[2], to[2], progress)]
+}
+fn lerp_stops<T: Copy, const N: usize>(from: &[T;N], to: &[T;N], lerp: impl Fn(T, T) -> T) -> [T;N] {
+    let mut stops = *from;
+    for (stop, to) in stops.iter_mut().zip(to) {
+        *stop = lerp(*stop, *to);
+    }
+    stops
+}
+fn lerp_color(from: &Color, to: &Color, progress: f32) -> Option<Color> {
+    let lerp = |from, to| lerp_rgb(from, to, progress);
+    Some(match (from, to) {
+        (Color::Mono(from), Color::Mono(to)) => Color::Mono(lerp(*from, *to)),
+        (Color::Linear(from), Color::Linear(to)) => Color::Linear(lerp_stops(from, to, lerp)),
+        (Color::LinearWithStop(from), Color::LinearWithStop(to)) => Color::LinearWithStop(lerp_stops(from, to, lerp)),
+        (Color::Corners(from), Color::Corners(to)) => Color::Corners(lerp_stops(from, to, lerp)),
+        (Color::CornersWithStop(from), Color::CornersWithStop(to)) => Color::CornersWithStop(lerp_stops(from, to, lerp)),
+        _ => return None
+    })
+}
+fn lerp_alpha(from: &Alpha, to: &Alpha, progress: f32) -> Option<Alpha> {
+    let lerp = |from, to| lerp_byte(from, to, progress);
+    Some(match (from, to) {
+        (Alpha::Mono(from), Alpha::Mono(to)) => Alpha::Mono(lerp(*from, *to)),
+        (Alpha::Linear(from), Alpha::Linear(to)) => Alpha::Linear(lerp_stops(from, to, lerp)),
+        (Alpha::LinearWithStop(from), Alpha::LinearWithStop(to)) => Alpha::LinearWithStop(lerp_stops(from, to, lerp)),
+        (Alpha::Corners(from), Alpha::Corners(to)) => Alpha::Corners(lerp_stops(from, to, lerp)),
+        (Alpha::CornersWithStop(from), Alpha::CornersWithStop(to)) => Alpha::CornersWithStop(lerp_stops(from, to, lerp)),
+        _ => return None
+    })
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{StyleState,EventObject,Border,TransformStack,Color,Alpha,Scale,Point3D};
+
+    #[test]
+    fn style_apply() {
+        let mut style = StyleState::default();
+        style.apply(&EventObject::TagSize(42.5));
+        style.apply(&EventObject::TagBorder(Border::All(2.0, 3.0)));
+        style.apply(&EventObject::TagBorder(Border::Vertical(1.0)));
+        style.apply(&EventObject::TagScale(Scale::X(2.0)));
+        assert_eq!(style.size, 42.5);
+        assert_eq!(style.border, (2.0, 1.0));
+        let mut transform = TransformStack::default();
+        transform.scale(&Scale::X(2.0));
+        assert_eq!(style.transform, transform);
+        style.apply(&EventObject::TagReset);
+        assert_eq!(style.transform, TransformStack::default());
+    }
+
+    #[test]
+    fn style_animate() {
+        let mut style = StyleState::default();
+        style.apply(&EventObject::TagColor(Color::Linear([[0, 0, 0], [200, 100, 0]])));
+        style.apply(&EventObject::TagPosition(Point3D {x: 10.0, y: 0.0, z: 0.0}));
+        let tags = [
+            EventObject::TagSize(50.0),
+            EventObject::TagColor(Color::Linear([[100, 100, 100], [0, 0, 0]])),
+            EventObject::TagAlpha(Alpha::Linear([0, 0])),
+            EventObject::TagPosition(Point3D {x: 20.0, y: 10.0, z: 0.0}),
+            EventObject::TagScale(Scale::X(3.0)),
+            EventObject::TagBold(true)
+        ];
+        let mut halfway = style.clone();
+        halfway.animate(&tags, 0.5);
+        assert_eq!(halfway.size, 40.0);
+        assert_eq!(halfway.color, Color::Linear([[50, 50, 50], [100, 50, 0]]));
+        assert_eq!(halfway.alpha, Alpha::Mono(255));
+        assert_eq!(halfway.position, Some(Point3D {x: 15.0, y: 5.0, z: 0.0}));
+        let mut transform = TransformStack::default();
+        transform.scale(&Scale::X(2.0));
+        assert_eq!(halfway.transform, transform);
+        assert!(!halfway.bold);
+        let mut finished = style;
+        finished.animate(&tags, 1.0);
+        assert_eq!((finished.size, finished.bold, finished.alpha), (50.0, true, Alpha::Linear([0, 0])));
+        // Position without start moves from default anchor
+        let mut unpositioned = StyleState::default();
+        unpositioned.animate(&tags, 0.5);
+        assert_eq!(unpositioned.position, None);
+        assert_eq!(unpositioned.anchor(Point3D {x: 0.0, y: 20.0, z: 0.0}), Point3D {x: 10.0, y: 15.0, z: 0.0});
+        unpositioned.animate(&tags, 1.0);
+        assert_eq!(unpositioned.anchor(Point3D {x: 0.0, y: 20.0, z: 0.0}), Point3D {x: 20.0, y: 10.0, z: 0.0});
+    }
+
+    #[test]
+    fn style_karaoke() {
+        let mut style = StyleState::default();
+        assert_eq!(style.geometry_style(Some(0)).color, style.color);
+        style.apply(&EventObject::TagKaraokeColor([255, 0, 0]));
+        style.apply(&EventObject::TagKaraoke(100));
+        assert_eq!(style.geometry_style(Some(0)).color, Color::Mono([255, 0, 0]));
+        style.apply(&EventObject::TagKaraoke(200));
+        assert_eq!((style.karaoke, style.karaoke_position), (Some(100), 300));
+        assert_eq!(style.geometry_style(Some(99)).color, style.color);
+        assert_eq!(style.geometry_style(None).color, Color::Mono([255, 0, 0]));
+        style.apply(&EventObject::TagKaraokeSet(-50));
+        style.apply(&EventObject::TagKaraoke(100));
+        assert_eq!((style.karaoke, style.karaoke_position), (Some(-50), 50));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,203 @@
+// Imports
+use ssb_parser::objects::{
+    ssb_objects::View,
+    event_objects::{Point3D,Coordinate,Rotate,Scale,Translate,Shear}
+};
+use puny2d::vector::point::Point;
+
+
+// Smallest distance of points in front of camera (avoids division by zero & mirroring behind camera)
+const MIN_CAMERA_DISTANCE: Coordinate = 1e-3;
+
+// Row-major 4x4 identity
+const IDENTITY: [Coordinate; 16] = [
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 1.0, 0.0,
+    0.0, 0.0, 0.0, 1.0
+];
+
+/// Stack of 3d transformations, composed in tag order (later transformations apply first on points, like nested coordinate systems).
+#[derive(Debug, PartialEq, Clone)]
+pub struct TransformStack {
+    matrix: [Coordinate; 16]
+}
+impl Default for TransformStack {
+    fn default() -> Self {
+        Self {
+            matrix: IDENTITY
+        }
+    }
+}
+impl TransformStack {
+    /// Pushes rotation (in degrees) around axis.
+    pub fn rotate(&mut self, rotate: &Rotate) {
+        self.push(match rotate {
+            Rotate::X(angle) => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                [
+                    1.0, 0.0, 0.0, 0.0,
+                    0.0, cos, -sin, 0.0,
+                    0.0, sin, cos, 0.0,
+                    0.0, 0.0, 0.0, 1.0
+                ]
+            }
+            Rotate::Y(angle) => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                [
+                    cos, 0.0, sin, 0.0,
+                    0.0, 1.0, 0.0, 0.0,
+                    -sin, 0.0, cos, 0.0,
+                    0.0, 0.0, 0.0, 1.0
+                ]
+            }
+            Rotate::Z(angle) => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                [
+                    cos, -sin, 0.0, 0.0,
+                    sin, cos, 0.0, 0.0,
+                    0.0, 0.0, 1.0, 0.0,
+                    0.0, 0.0, 0.0, 1.0
+                ]
+            }
+        });
+    }
+    /// Pushes scale.
+    pub fn scale(&mut self, scale: &Scale) {
+        let (x, y, z) = match scale {
+            Scale::All(x, y, z) => (*x, *y, *z),
+            Scale::X(x) => (*x, 1.0, 1.0),
+            Scale::Y(y) => (1.0, *y, 1.0),
+            Scale::Z(z) => (1.0, 1.0, *z)
+        };
+        self.push([
+            x, 0.0, 0.0, 0.0,
+            0.0, y, 0.0, 0.0,
+            0.0, 0.0, z, 0.0,
+            0.0, 0.0, 0.0, 1.0
+        ]);
+    }
+    /// Pushes translation.
+    pub fn translate(&mut self, translate: &Translate) {
+        let (x, y, z) = match translate {
+            Translate::All(x, y, z) => (*x, *y, *z),
+            Translate::X(x) => (*x, 0.0, 0.0),
+            Translate::Y(y) => (0.0, *y, 0.0),
+            Translate::Z(z) => (0.0, 0.0, *z)
+        };
+        self.push([
+            1.0, 0.0, 0.0, x,
+            0.0, 1.0, 0.0, y,
+            0.0, 0.0, 1.0, z,
+            0.0, 0.0, 0.0, 1.0
+        ]);
+    }
+    /// Pushes shear.
+    pub fn shear(&mut self, shear: &Shear) {
+        let (x, y) = match shear {
+            Shear::All(x, y) => (*x, *y),
+            Shear::X(x) => (*x, 0.0),
+            Shear::Y(y) => (0.0, *y)
+        };
+        self.push([
+            1.0, x, 0.0, 0.0,
+            y, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0
+        ]);
+    }
+    /// Pushes arbitrary matrix (row-major).
+    pub fn push(&mut self, matrix: [Coordinate; 16]) {
+        let mut product = [0.0; 16];
+        for row in 0..4 {
+            for column in 0..4 {
+                product[row * 4 + column] = (0..4).map(|index| self.matrix[row * 4 + index] * matrix[index * 4 + column]).sum();
+            }
+        }
+        self.matrix = product;
+    }
+    /// Removes all transformations.
+    pub fn reset(&mut self) {
+        self.matrix = IDENTITY;
+    }
+    /// Transforms point on plane z=0 into 3d space.
+    pub fn transform(&self, point: Point) -> Point3D {
+        let m = &self.matrix;
+        let (x, y, z, w) = (
+            m[0] * point.x + m[1] * point.y + m[3],
+            m[4] * point.x + m[5] * point.y + m[7],
+            m[8] * point.x + m[9] * point.y + m[11],
+            m[12] * point.x + m[13] * point.y + m[15]
+        );
+        if w == 1.0 || w == 0.0 {
+            Point3D {x, y, z}
+        } else {
+            Point3D {x: x / w, y: y / w, z: z / w}
+        }
+    }
+}
+
+/// Projection of 3d space on frame by view.
+/// Perspective view looks from frame center at depth distance in front of frame (z=0), positive z goes into frame.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Camera {
+    pub view: View,
+    pub depth: Coordinate,
+    pub center: Point
+}
+impl Camera {
+    /// Projects point on frame plane.
+    pub fn project(&self, point: &Point3D) -> Point {
+        match self.view {
+            View::Perspective if self.depth > 0.0 => {
+                let factor = self.depth / (self.depth + point.z).max(MIN_CAMERA_DISTANCE);
+                Point {
+                    x: self.center.x + (point.x - self.center.x) * factor,
+                    y: self.center.y + (point.y - self.center.y) * factor
+                }
+            }
+            _ => Point {x: point.x, y: point.y}
+        }
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{TransformStack,Camera,View,Point,Point3D,Rotate,Scale,Translate};
+
+    #[test]
+    fn transform_order() {
+        let mut stack = TransformStack::default();
+        stack.translate(&Translate::X(10.0));
+        stack.scale(&Scale::All(2.0, 3.0, 1.0));
+        assert_eq!(stack.transform(Point {x: 1.0, y: 1.0}), Point3D {x: 12.0, y: 3.0, z: 0.0});
+        stack.reset();
+        assert_eq!(stack, TransformStack::default());
+        stack.rotate(&Rotate::Y(90.0));
+        let point = stack.transform(Point {x: 1.0, y: 0.0});
+        assert!(point.x.abs() < 1e-6 && point.y == 0.0 && (point.z + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn transform_projective() {
+        let mut stack = TransformStack::default();
+        stack.push([
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 2.0
+        ]);
+        assert_eq!(stack.transform(Point {x: 4.0, y: 2.0}), Point3D {x: 2.0, y: 1.0, z: 0.0});
+    }
+
+    #[test]
+    fn camera_views() {
+        let mut camera = Camera {view: View::Perspective, depth: 100.0, center: Point {x: 50.0, y: 50.0}};
+        assert_eq!(camera.project(&Point3D {x: 150.0, y: 50.0, z: 100.0}), Point {x: 100.0, y: 50.0});
+        assert_eq!(camera.project(&Point3D {x: 0.0, y: 0.0, z: 0.0}), Point {x: 0.0, y: 0.0});
+        camera.view = View::Orthogonal;
+        assert_eq!(camera.project(&Point3D {x: 150.0, y: 50.0, z: 100.0}), Point {x: 150.0, y: 50.0});
+    }
+}
\ No newline at end of file
@@ -0,0 +1,161 @@
+// Imports
+use std::{
+    collections::{HashMap,BTreeSet},
+    fs,
+    path::Path,
+    sync::Arc
+};
+use ssb_parser::objects::ssb_objects::{FontFace,FontStyle,FontData};
+use rustybuzz::Face;
+use crate::error::RenderingError;
+use super::shape::Synthesis;
+
+
+// File extensions of fonts in local directories
+const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "ttc", "otc"];
+
+// Font data with index of face (for font collections)
+#[derive(Debug, PartialEq, Clone)]
+struct FontSource {
+    data: Arc<FontData>,
+    index: u32
+}
+
+/// Font faces by family & style. Embedded fonts take precedence over local ones.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct FontCollection {
+    faces: HashMap<FontFace, FontSource>
+}
+impl FontCollection {
+    /// Takes embedded fonts as base.
+    pub fn new(fonts: HashMap<FontFace, FontData>) -> Self {
+        Self {
+            faces: fonts.into_iter()
+                .map(|(face, data)| (face, FontSource {data: Arc::new(data), index: 0}))
+                .collect()
+        }
+    }
+    /// Adds fonts of local directory (recursively) without replacing known faces.
+    /// Files which aren't valid fonts get skipped.
+    pub fn load_directory(&mut self, directory: &Path) -> Result<(), RenderingError> {
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.load_directory(&path)?;
+            } else if matches!(path.extension().and_then(|extension| extension.to_str()), Some(extension) if FONT_EXTENSIONS.iter().any(|font_extension| font_extension.eq_ignore_ascii_case(extension))) {
+                let data = Arc::new(fs::read(&path)?);
+                for index in 0..ttf_parser::fonts_in_collection(&data).unwrap_or(1) {
+                    if let Some(face) = Face::from_slice(&data, index).as_ref().and_then(font_face) {
+                        self.faces.entry(face).or_insert_with(|| FontSource {data: data.clone(), index});
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Parsed font by family & style.
+    pub fn get(&self, face: &FontFace) -> Option<Face<'_>> {
+        self.faces.get(face).and_then(|source| Face::from_slice(&source.data, source.index))
+    }
+    /// Parsed font by family & closest available style with synthesis to reach requested style.
+    /// Fallback order prefers styles only missing weight or slant (synthesized), then styles with unrequested weight or slant:
+    /// * Regular: Regular, Bold, Italic, BoldItalic
+    /// * Bold: Bold, Regular (emboldened), BoldItalic, Italic (emboldened)
+    /// * Italic: Italic, Regular (obliqued), BoldItalic, Bold (obliqued)
+    /// * BoldItalic: BoldItalic, Bold (obliqued), Italic (emboldened), Regular (emboldened & obliqued)
+    pub fn get_closest(&self, face: &FontFace) -> Option<(Face<'_>, Synthesis)> {
+        let (bold, italic) = style_traits(&face.style);
+        fallback_styles(&face.style).iter()
+            .find_map(|style| {
+                let (style_bold, style_italic) = style_traits(style);
+                self.get(&FontFace {family: face.family.clone(), style: style.clone()})
+                    .map(|found| (found, Synthesis {embolden: bold && !style_bold, oblique: italic && !style_italic}))
+            })
+    }
+    /// Parsed font of first family (alphabetically) by closest available style, as replacement for missing families.
+    pub fn get_replacement(&self, style: &FontStyle) -> Option<(Face<'_>, Synthesis)> {
+        self.faces.keys()
+            .map(|face| &face.family)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .find_map(|family| self.get_closest(&FontFace {family: family.clone(), style: style.clone()}))
+    }
+}
+
+// Styles to search for requested style, closest first
+fn fallback_styles(style: &FontStyle) -> [FontStyle; 4] {
+    match style {
+        FontStyle::Regular => [FontStyle::Regular, FontStyle::Bold, FontStyle::Italic, FontStyle::BoldItalic],
+        FontStyle::Bold => [FontStyle::Bold, FontStyle::Regular, FontStyle::BoldItalic, FontStyle::Italic],
+        FontStyle::Italic => [FontStyle::Italic, FontStyle::Regular, FontStyle::BoldItalic, FontStyle::Bold],
+        FontStyle::BoldItalic => [FontStyle::BoldItalic, FontStyle::Bold, FontStyle::Italic, FontStyle::Regular]
+    }
+}
+
+// Weight & slant of style
+fn style_traits(style: &FontStyle) -> (bool, bool) {
+    match style {
+        FontStyle::Regular => (false, false),
+        FontStyle::Bold => (true, false),
+        FontStyle::Italic => (false, true),
+        FontStyle::BoldItalic => (true, true)
+    }
+}
+
+// Family & style from font tables
+fn font_face(face: &Face) -> Option<FontFace> {
+    Some(FontFace {
+        family: face.names().find_map(|name| if name.name_id() == ttf_parser::name_id::FAMILY {name.to_string()} else {None})?,
+        style: match (face.is_bold(), face.is_italic()) {
+            (false, false) => FontStyle::Regular,
+            (true, false) => FontStyle::Bold,
+            (false, true) => FontStyle::Italic,
+            (true, true) => FontStyle::BoldItalic
+        }
+    })
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{FontCollection,FontFace,FontStyle,Synthesis};
+    use std::collections::HashMap;
+    use crate::text::common::{test_font,test_font_directory};
+
+    #[test]
+    fn font_directory() {
+        let mut fonts = FontCollection::default();
+        let directory = test_font_directory();
+        fonts.load_directory(directory).expect("Font directory should be readable!");
+        assert!(
+            fonts.get(&FontFace {family: "DejaVu Sans".to_owned(), style: FontStyle::Bold}).is_some() ||
+            fonts.get(&FontFace {family: "Arial".to_owned(), style: FontStyle::Bold}).is_some()
+        );
+        assert!(fonts.get(&FontFace {family: "Unknown".to_owned(), style: FontStyle::Regular}).is_none());
+    }
+
+    #[test]
+    fn font_embedded_invalid() {
+        let mut embedded = HashMap::new();
+        embedded.insert(FontFace {family: "Broken".to_owned(), style: FontStyle::Regular}, vec![0u8; 16]);
+        assert!(FontCollection::new(embedded).get(&FontFace {family: "Broken".to_owned(), style: FontStyle::Regular}).is_none());
+    }
+
+    #[test]
+    fn font_closest_style() {
+        let data = test_font();
+        let mut embedded = HashMap::new();
+        embedded.insert(FontFace {family: "Test".to_owned(), style: FontStyle::Bold}, data.clone());
+        embedded.insert(FontFace {family: "Test".to_owned(), style: FontStyle::Italic}, data);
+        let fonts = FontCollection::new(embedded);
+        let closest = |style: FontStyle| fonts.get_closest(&FontFace {family: "Test".to_owned(), style}).map(|(_, synthesis)| synthesis);
+        assert_eq!(closest(FontStyle::Regular), Some(Synthesis::default()));
+        assert_eq!(closest(FontStyle::Bold), Some(Synthesis::default()));
+        assert_eq!(closest(FontStyle::Italic), Some(Synthesis::default()));
+        assert_eq!(closest(FontStyle::BoldItalic), Some(Synthesis {embolden: false, oblique: true}));
+        assert!(fonts.get_closest(&FontFace {family: "Unknown".to_owned(), style: FontStyle::Regular}).is_none());
+        assert_eq!(fonts.get_replacement(&FontStyle::BoldItalic).map(|(_, synthesis)| synthesis), closest(FontStyle::BoldItalic));
+        assert!(FontCollection::default().get_replacement(&FontStyle::Regular).is_none());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,431 @@
+// Imports
+use std::ops::Range;
+use ssb_parser::objects::event_objects::{Direction,WrapStyle};
+use rustybuzz::{Face,Direction as ShapeDirection,Script,Tag};
+use unicode_bidi::{BidiInfo,Level};
+use unicode_script::{UnicodeScript,Script as CharScript};
+use unicode_linebreak::{linebreaks,BreakOpportunity};
+use unicode_segmentation::UnicodeSegmentation;
+use puny2d::vector::{
+    types::Coordinate,
+    point::Point,
+    path::{PathBase,Path}
+};
+use super::shape::{ShapedGlyph,GlyphCache,LineMetrics,DecorationMetrics,Synthesis,shape_text,line_metrics,decoration_metrics,glyphs_to_path,outline_orientation};
+
+
+// Iterations to search the narrowest line length of balanced wrapping
+const BALANCE_ITERATIONS: usize = 16;
+
+/// Text in block with own font (and synthetic style), size, letter spacing (along line) & decorations.
+pub struct TextRun<'a> {
+    pub face: Face<'a>,
+    pub synthesis: Synthesis,
+    pub text: &'a str,
+    pub size: f32,
+    pub letter_spacing: f32,
+    pub underline: bool,
+    pub strikeout: bool
+}
+
+/// Layout options of text block.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BlockOptions {
+    pub direction: Direction,
+    pub wrap_style: WrapStyle,
+    pub max_line_length: Option<f32>,
+    pub balanced: bool,
+    pub line_alignment: f32,
+    pub line_spacing: f32
+}
+
+/// Laid out text block with outlines per run. Block starts at origin (horizontal: top-left corner, vertical: top-right corner as columns go leftwards).
+#[derive(Debug, PartialEq, Clone)]
+pub struct TextBlock {
+    pub paths: Vec<Path>,
+    pub width: f32,
+    pub height: f32
+}
+
+/// Lays out text runs into lines (horizontal directions: downwards, vertical directions: columns leftwards).
+/// Lines break at newlines and get wrapped by style & maximal length (width or height by direction).
+/// Letter spacing follows every cluster, line spacing lies between lines.
+/// Balanced wrapping keeps the number of lines but shortens them to similar length.
+/// Line alignment moves shorter lines from start (0) over center (0.5) to end (1) of block.
+/// Decorations become part of run outlines (vertical lines get them like horizontal lines turned clockwise).
+/// Glyph outlines get taken from (and added to) cache.
+pub fn layout_block(runs: &[TextRun], options: &BlockOptions, glyph_cache: &GlyphCache) -> TextBlock {
+    let vertical = matches!(options.direction, Direction::TopToBottom | Direction::BottomToTop);
+    let advance_length = |glyph: &ShapedGlyph| if vertical {glyph.advance.y} else {glyph.advance.x};
+    // Concatenate runs for line breaking over style changes
+    let text = runs.iter().map(|run| run.text).collect::<String>();
+    let run_starts = runs.iter().scan(0, |start, run| {
+        let run_start = *start;
+        *start += run.text.len();
+        Some(run_start)
+    }).collect::<Vec<_>>();
+    // Measure clusters by advances of logical shaping
+    let mut advance_sums = vec![0.0; text.len() + 1];
+    for (run, run_start) in runs.iter().zip(&run_starts) {
+        for glyph in space_glyphs(shape_line(&run.face, run.text, run.size, &options.direction), run.letter_spacing + run.synthesis.advance(run.size), vertical) {
+            advance_sums[run_start + glyph.cluster + 1] += advance_length(&glyph);
+        }
+    }
+    for index in 1..advance_sums.len() {
+        advance_sums[index] += advance_sums[index - 1];
+    }
+    let measure = |line: &Range<usize>| advance_sums[trim_line(&text, line).end] - advance_sums[line.start];
+    // Break text into lines
+    let segments = break_segments(&text, &options.wrap_style);
+    let mut lines = wrap_lines(&segments, &measure, options.max_line_length);
+    if let (true, Some(max_line_length)) = (options.balanced, options.max_line_length) {
+        if lines.len() > 1 {
+            let (mut narrow, mut wide) = (0.0, max_line_length);
+            for _ in 0..BALANCE_ITERATIONS {
+                let length = (narrow + wide) * 0.5;
+                if wrap_lines(&segments, &measure, Some(length)).len() > lines.len() {
+                    narrow = length;
+                } else {
+                    wide = length;
+                }
+            }
+            lines = wrap_lines(&segments, &measure, Some(wide));
+        }
+    }
+    // Shape line pieces of runs
+    let lines = lines.iter()
+        .map(|line| {
+            let line = trim_line(&text, line);
+            // Resolve directions over whole line, split into pieces of runs in visual order
+            let pieces = direction_runs(&text[line.clone()], &options.direction).into_iter()
+                .flat_map(|(direction_run, run_direction)| {
+                    let (start, end) = (line.start + direction_run.start, line.start + direction_run.end);
+                    let mut run_pieces = run_starts.iter().enumerate()
+                        .filter_map(|(run_index, run_start)| {
+                            let (start, end) = (start.max(*run_start), end.min(run_start + runs[run_index].text.len()));
+                            if start < end {Some((run_index, start - run_start..end - run_start))} else {None}
+                        })
+                        .collect::<Vec<_>>();
+                    if let ShapeDirection::RightToLeft | ShapeDirection::BottomToTop = run_direction {
+                        run_pieces.reverse();
+                    }
+                    run_pieces.into_iter().map(move |(run_index, piece)| (run_index, piece, run_direction))
+                })
+                .map(|(run_index, piece, run_direction)| {
+                    let run = &runs[run_index];
+                    (run_index, space_glyphs(shape_run(&run.face, &run.text[piece], run.size, run_direction), run.letter_spacing + run.synthesis.advance(run.size), vertical))
+                })
+                .collect::<Vec<_>>();
+            // Empty lines take metrics of run they're in
+            let metrics = pieces.iter()
+                .map(|(run_index, _)| *run_index)
+                .chain(if pieces.is_empty() {run_starts.iter().rposition(|run_start| *run_start <= line.start)} else {None})
+                .map(|run_index| line_metrics(&runs[run_index].face, runs[run_index].size))
+                .fold(LineMetrics {ascent: 0.0, descent: 0.0, height: 0.0}, |metrics, run_metrics| LineMetrics {
+                    ascent: metrics.ascent.max(run_metrics.ascent),
+                    descent: metrics.descent.max(run_metrics.descent),
+                    height: metrics.height.max(run_metrics.height)
+                });
+            let length = pieces.iter().flat_map(|(_, glyphs)| glyphs).map(advance_length).sum::<f32>();
+            (pieces, metrics, length)
+        })
+        .collect::<Vec<_>>();
+    // Place lines
+    let thickness = lines.iter().map(|(_, metrics, _)| metrics.height).sum::<f32>() + options.line_spacing * lines.len().saturating_sub(1) as f32;
+    let length = lines.iter().map(|(_, _, length)| *length).fold(0.0, f32::max);
+    let mut paths = vec![Path::default(); runs.len()];
+    let mut line_offset = 0.0;
+    for (pieces, metrics, line_length) in lines {
+        let alignment_offset = (length - line_length) * options.line_alignment;
+        let mut pen = if vertical {
+            Point {x: -line_offset - metrics.height * 0.5, y: alignment_offset}
+        } else {
+            Point {x: alignment_offset, y: line_offset + metrics.ascent}
+        };
+        for (run_index, glyphs) in pieces {
+            let run = &runs[run_index];
+            let (piece_start, segments_start) = (pen, paths[run_index].segments().len());
+            pen = glyphs_to_path(&run.face, &glyphs, run.size, run.synthesis, pen, &mut paths[run_index], glyph_cache);
+            if run.underline || run.strikeout {
+                // Decorations oriented like glyph outlines to unite with them by non-zero winding
+                let orientation = outline_orientation(&paths[run_index].segments()[segments_start..]);
+                let (underline, strikeout) = decoration_metrics(&run.face, run.size);
+                let run_metrics = line_metrics(&run.face, run.size);
+                for decoration in [(run.underline, underline), (run.strikeout, strikeout)].iter().filter(|(enabled, _)| *enabled).map(|(_, decoration)| decoration) {
+                    add_decoration(&mut paths[run_index], piece_start, pen, decoration, if vertical {Some(&run_metrics)} else {None}, orientation);
+                }
+            }
+        }
+        line_offset += metrics.height + options.line_spacing;
+    }
+    TextBlock {
+        paths,
+        width: if vertical {thickness} else {length},
+        height: if vertical {length} else {thickness}
+    }
+}
+
+// Adds spacing to advance of last glyph per cluster
+fn space_glyphs(mut glyphs: Vec<ShapedGlyph>, spacing: f32, vertical: bool) -> Vec<ShapedGlyph> {
+    if spacing != 0.0 {
+        for index in 0..glyphs.len() {
+            if !matches!(glyphs.get(index + 1), Some(next) if next.cluster == glyphs[index].cluster) {
+                if vertical {
+                    glyphs[index].advance.y += spacing;
+                } else {
+                    glyphs[index].advance.x += spacing;
+                }
+            }
+        }
+    }
+    glyphs
+}
+
+// Rectangle of decoration along line piece from start to end pen (on baseline or column center)
+fn add_decoration(path: &mut Path, start: Point, end: Point, decoration: &DecorationMetrics, vertical_metrics: Option<&LineMetrics>, orientation: Coordinate) {
+    let (min, max) = match vertical_metrics {
+        Some(metrics) => {
+            let offset = decoration.position - (metrics.ascent - metrics.descent) * 0.5;
+            (Point {x: start.x + offset - decoration.thickness, y: start.y}, Point {x: start.x + offset, y: end.y})
+        }
+        None => (Point {x: start.x, y: start.y - decoration.position}, Point {x: end.x, y: start.y - decoration.position + decoration.thickness})
+    };
+    if min.x == max.x || min.y == max.y {
+        return;
+    }
+    let mut corners = [min, Point {x: max.x, y: min.y}, max, Point {x: min.x, y: max.y}];
+    if orientation < 0.0 {
+        corners.reverse();
+    }
+    path.move_to(corners[0]);
+    for corner in &corners[1..] {
+        path.line_to(*corner);
+    }
+    path.close();
+}
+
+// Text segments between break opportunities, flagged by mandatory break at end
+fn break_segments(text: &str, wrap_style: &WrapStyle) -> Vec<(Range<usize>, bool)> {
+    let breaks: Vec<(usize, bool)> = match wrap_style {
+        WrapStyle::Space => linebreaks(text)
+            .map(|(index, opportunity)| (index, opportunity == BreakOpportunity::Mandatory))
+            .collect(),
+        WrapStyle::Character => text.grapheme_indices(true)
+            .map(|(index, grapheme)| (index + grapheme.len(), grapheme.ends_with('\n')))
+            .collect(),
+        WrapStyle::NoWrap => text.match_indices('\n')
+            .map(|(index, _)| (index + 1, true))
+            .chain(std::iter::once((text.len(), true)))
+            .collect()
+    };
+    breaks.into_iter()
+        .scan(0, |start, (end, mandatory)| {
+            let segment = (*start..end, mandatory);
+            *start = end;
+            Some(segment)
+        })
+        .filter(|(segment, _)| !segment.is_empty())
+        .collect()
+}
+
+// Greedy line wrapping of segments by measured length
+fn wrap_lines<F>(segments: &[(Range<usize>, bool)], measure: &F, max_line_length: Option<f32>) -> Vec<Range<usize>>
+    where F: Fn(&Range<usize>) -> f32 {
+    let mut lines = vec![];
+    let mut line: Option<Range<usize>> = None;
+    for (segment, mandatory) in segments {
+        line = Some(match line {
+            Some(line) if !matches!(max_line_length, Some(max_line_length) if measure(&(line.start..segment.end)) > max_line_length) => line.start..segment.end,
+            Some(line) => {
+                lines.push(line);
+                segment.clone()
+            }
+            None => segment.clone()
+        });
+        if *mandatory {
+            lines.extend(line.take());
+        }
+    }
+    lines.extend(line);
+    lines
+}
+
+// Line without trailing whitespace & newline
+fn trim_line(text: &str, line: &Range<usize>) -> Range<usize> {
+    line.start..line.start + text[line.clone()].trim_end().len()
+}
+
+
+/// Shapes line of text in visual order.
+/// Horizontal directions resolve bidirectional runs with direction as paragraph level, vertical directions set glyphs upright.
+/// Runs get split by script for complex script shaping. Clusters are byte indices into line.
+pub fn shape_line(face: &Face, line: &str, size: f32, direction: &Direction) -> Vec<ShapedGlyph> {
+    direction_runs(line, direction).into_iter()
+        .flat_map(|(run, run_direction)|
+            shape_run(face, &line[run.clone()], size, run_direction).into_iter()
+            .map(move |glyph| ShapedGlyph {cluster: glyph.cluster + run.start, ..glyph})
+        )
+        .collect()
+}
+
+// Splits line into runs of same direction in visual order (horizontal directions by bidirectional levels with direction as paragraph level)
+fn direction_runs(line: &str, direction: &Direction) -> Vec<(Range<usize>, ShapeDirection)> {
+    match direction {
+        Direction::LeftToRight | Direction::RightToLeft => {
+            let bidi_info = BidiInfo::new(line, Some(if let Direction::RightToLeft = direction {Level::rtl()} else {Level::ltr()}));
+            bidi_info.paragraphs.iter()
+                .flat_map(|paragraph| {
+                    let (levels, runs) = bidi_info.visual_runs(paragraph, paragraph.range.clone());
+                    runs.into_iter()
+                        .map(|run| {
+                            let run_direction = if levels[run.start].is_rtl() {ShapeDirection::RightToLeft} else {ShapeDirection::LeftToRight};
+                            (run, run_direction)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        }
+        Direction::TopToBottom => vec![(0..line.len(), ShapeDirection::TopToBottom)],
+        Direction::BottomToTop => vec![(0..line.len(), ShapeDirection::BottomToTop)]
+    }
+}
+
+// Shapes text of one direction in visual order, split by script
+fn shape_run(face: &Face, text: &str, size: f32, direction: ShapeDirection) -> Vec<ShapedGlyph> {
+    let mut runs = script_runs(text);
+    if let ShapeDirection::RightToLeft | ShapeDirection::BottomToTop = direction {
+        runs.reverse();
+    }
+    runs.into_iter()
+        .flat_map(|(script_run, script)|
+            shape_text(face, &text[script_run.clone()], size, direction, script).into_iter()
+            .map(move |glyph| ShapedGlyph {cluster: glyph.cluster + script_run.start, ..glyph})
+        )
+        .collect()
+}
+
+// Splits text into runs of same script (common & inherited characters join their neighbours)
+fn script_runs(text: &str) -> Vec<(Range<usize>, Option<Script>)> {
+    let is_neutral = |script: CharScript| matches!(script, CharScript::Common | CharScript::Inherited | CharScript::Unknown);
+    let mut runs: Vec<(Range<usize>, CharScript)> = vec![];
+    for (index, character) in text.char_indices() {
+        let (end, script) = (index + character.len_utf8(), character.script());
+        match runs.last_mut() {
+            Some((run, run_script)) if script == *run_script || is_neutral(script) => run.end = end,
+            Some((run, run_script)) if is_neutral(*run_script) => {
+                run.end = end;
+                *run_script = script;
+            }
+            _ => runs.push((index..end, script))
+        }
+    }
+    runs.into_iter()
+        .map(|(run, script)| (
+            run,
+            if is_neutral(script) {None} else {Script::from_iso15924_tag(Tag::from_bytes_lossy(script.short_name().as_bytes()))}
+        ))
+        .collect()
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{script_runs,shape_line,space_glyphs,break_segments,wrap_lines,layout_block,TextRun,BlockOptions,Synthesis,Face,Direction,WrapStyle,Script,Path,GlyphCache};
+    use puny2d::vector::path::FlatPath;
+    use crate::text::common::test_font;
+
+    #[test]
+    fn script_runs_split() {
+        let runs = script_runs("Hi, नमस्ते! 1");
+        assert_eq!(runs.len(), 2);
+        assert_eq!((runs[0].0.clone(), runs[0].1), (0..4, Script::from_iso15924_tag(rustybuzz::Tag::from_bytes(b"Latn"))));
+        assert_eq!(runs[1].0, 4.."Hi, नमस्ते! 1".len());
+        assert_eq!(script_runs("123"), vec![(0..3, None)]);
+    }
+
+    #[test]
+    fn shape_line_directions() {
+        let data = test_font();
+        let face = Face::from_slice(&data, 0).expect("Test font should be valid!");
+        // Hebrew run gets reversed into visual order, clusters stay logical
+        let clusters = |direction| shape_line(&face, "ab אב", 20.0, &direction).iter().map(|glyph| glyph.cluster).collect::<Vec<_>>();
+        assert_eq!(clusters(Direction::LeftToRight), vec![0, 1, 2, 5, 3]);
+        assert_eq!(clusters(Direction::RightToLeft), vec![5, 3, 2, 0, 1]);
+        // Vertical advances go downwards
+        let glyphs = shape_line(&face, "ab", 20.0, &Direction::TopToBottom);
+        assert!(glyphs.iter().all(|glyph| glyph.advance.x == 0.0 && glyph.advance.y > 0.0));
+        assert_eq!(shape_line(&face, "ab", 20.0, &Direction::BottomToTop).iter().map(|glyph| glyph.cluster).collect::<Vec<_>>(), vec![1, 0]);
+    }
+
+    #[test]
+    fn layout_block_bidi_styles() {
+        let data = test_font();
+        // Latin word split over styles stays in logical order inside right-to-left line
+        let run = |text| TextRun {face: Face::from_slice(&data, 0).expect("Test font should be valid!"), synthesis: Synthesis::default(), text, size: 20.0, letter_spacing: 0.0, underline: false, strikeout: false};
+        let block = layout_block(
+            &[run("אב c"), run("d")],
+            &BlockOptions {direction: Direction::RightToLeft, wrap_style: WrapStyle::NoWrap, max_line_length: None, balanced: false, line_alignment: 0.0, line_spacing: 0.0},
+            &GlyphCache::default()
+        );
+        let bounding = |path: &Path| FlatPath::from(path.clone()).bounding().expect("Run should have an outline!");
+        let (first, second) = (bounding(&block.paths[0]), bounding(&block.paths[1]));
+        assert!(first.0.x < second.0.x && second.1.x < first.1.x, "Second run should lie between latin start & hebrew end of first run!");
+    }
+
+    #[test]
+    fn break_segments_styles() {
+        let text = "ab cd\nef";
+        assert_eq!(break_segments(text, &WrapStyle::Space), vec![(0..3, false), (3..6, true), (6..8, true)]);
+        assert_eq!(break_segments(text, &WrapStyle::Character).len(), 8);
+        assert_eq!(break_segments("e\u{301}f", &WrapStyle::Character), vec![(0..3, false), (3..4, false)]);
+        assert_eq!(break_segments(text, &WrapStyle::NoWrap), vec![(0..6, true), (6..8, true)]);
+    }
+
+    #[test]
+    fn wrap_lines_greedy() {
+        // Every byte measures one unit, trailing spaces don't count
+        let text = "aaa bb c dddd";
+        let segments = break_segments(text, &WrapStyle::Space);
+        let measure = |line: &std::ops::Range<usize>| text[line.clone()].trim_end().len() as f32;
+        assert_eq!(wrap_lines(&segments, &measure, Some(6.0)), vec![0..7, 7..13]);
+        assert_eq!(wrap_lines(&segments, &measure, Some(2.0)), vec![0..4, 4..7, 7..9, 9..13]);
+        assert_eq!(wrap_lines(&segments, &measure, None), vec![0..13]);
+    }
+
+    #[test]
+    fn layout_block_balanced() {
+        let data = test_font();
+        let layout = |text: &'static str, balanced: bool| layout_block(
+            &[TextRun {face: Face::from_slice(&data, 0).expect("Test font should be valid!"), synthesis: Synthesis::default(), text, size: 20.0, letter_spacing: 0.0, underline: false, strikeout: false}],
+            &BlockOptions {direction: Direction::LeftToRight, wrap_style: WrapStyle::Space, max_line_length: Some(300.0), balanced, line_alignment: 0.0, line_spacing: 0.0},
+            &GlyphCache::default()
+        );
+        let single_line = layout("word", false);
+        let greedy = layout("some words for a subtitle line which wraps", false);
+        let balanced = layout("some words for a subtitle line which wraps", true);
+        assert!((greedy.height - single_line.height * 2.0).abs() < 0.01 && greedy.height == balanced.height);
+        assert!(greedy.width <= 300.0 && balanced.width < greedy.width);
+        assert_eq!(layout("word\n\nword", false).height, single_line.height * 3.0);
+    }
+
+    #[test]
+    fn layout_block_spacing() {
+        let data = test_font();
+        let layout = |direction: Direction, letter_spacing: f32, line_spacing: f32| layout_block(
+            &[TextRun {face: Face::from_slice(&data, 0).expect("Test font should be valid!"), synthesis: Synthesis::default(), text: "ab\ncd", size: 20.0, letter_spacing, underline: false, strikeout: false}],
+            &BlockOptions {direction, wrap_style: WrapStyle::NoWrap, max_line_length: None, balanced: false, line_alignment: 0.0, line_spacing},
+            &GlyphCache::default()
+        );
+        let (plain, spaced) = (layout(Direction::LeftToRight, 0.0, 0.0), layout(Direction::LeftToRight, 3.0, 5.0));
+        assert!((spaced.width - plain.width - 6.0).abs() < 0.01 && (spaced.height - plain.height - 5.0).abs() < 0.01);
+        let (plain, spaced) = (layout(Direction::TopToBottom, 0.0, 0.0), layout(Direction::TopToBottom, 3.0, 5.0));
+        assert!((spaced.height - plain.height - 6.0).abs() < 0.01 && (spaced.width - plain.width - 5.0).abs() < 0.01);
+        // Clusters of multiple glyphs get spaced once
+        let face = Face::from_slice(&data, 0).expect("Test font should be valid!");
+        let glyphs = space_glyphs(shape_line(&face, "e\u{301}x", 20.0, &Direction::LeftToRight), 2.0, false);
+        let unspaced = shape_line(&face, "e\u{301}x", 20.0, &Direction::LeftToRight);
+        let advance = |glyphs: &[super::ShapedGlyph]| glyphs.iter().map(|glyph| glyph.advance.x).sum::<f32>();
+        assert!((advance(&glyphs) - advance(&unspaced) - 2.0 * 2.0).abs() < 0.01);
+    }
+}
\ No newline at end of file
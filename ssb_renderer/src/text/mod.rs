@@ -0,0 +1,10 @@
+// Font faces by embedded data & local directories.
+pub mod font;
+// Text shaping & glyph outlines.
+pub mod shape;
+// Bidirectional & complex script layout.
+pub mod layout;
+// Test helpers (shared with integration tests).
+#[cfg(test)]
+#[path = "../../tests/common/mod.rs"]
+pub mod common;
\ No newline at end of file
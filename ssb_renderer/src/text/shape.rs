@@ -0,0 +1,424 @@
+// Imports
+use std::{
+    fmt,
+    sync::{Arc,Mutex}
+};
+use lru::LruCache;
+use rustybuzz::{Face,UnicodeBuffer,Direction,Script,Tag};
+use ttf_parser::{GlyphId,OutlineBuilder};
+use puny2d::vector::{
+    types::Coordinate,
+    point::Point,
+    path::{PathBase,Path,PathSegment,FlatPathSegment}
+};
+
+
+// Slant of synthetic oblique style
+const OBLIQUE_DEGREES: f32 = 12.0;
+// Outline widening of synthetic bold style relative to font size
+const EMBOLDEN_FACTOR: f32 = 1.0 / 24.0;
+// Maximal count of cached glyph outlines
+const GLYPH_CACHE_ENTRIES: usize = 4096;
+
+
+/// Glyph of shaped text with metrics in pixels (y-axis down).
+#[derive(Debug, PartialEq, Clone)]
+pub struct ShapedGlyph {
+    pub id: u16,
+    pub cluster: usize,
+    pub advance: Point,
+    pub offset: Point
+}
+
+/// Vertical font metrics in pixels.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LineMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub height: f32
+}
+
+/// Text decoration line metrics in pixels: top edge above baseline & thickness.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DecorationMetrics {
+    pub position: f32,
+    pub thickness: f32
+}
+
+/// Synthetic styles for fonts missing requested weight or slant.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Synthesis {
+    pub embolden: bool,
+    pub oblique: bool
+}
+impl Synthesis {
+    /// Extra advance of glyphs for emboldened outlines.
+    pub fn advance(&self, size: f32) -> f32 {
+        if self.embolden {size * EMBOLDEN_FACTOR} else {0.0}
+    }
+}
+
+/// Pixels per font unit for font size (= pixels per em).
+pub fn font_scale(face: &Face, size: f32) -> f32 {
+    size / face.units_per_em() as f32
+}
+
+/// Shapes text by font features (like kerning & ligatures) in direction & script (guessed if unknown).
+/// Glyphs come in visual order, clusters are byte indices into text.
+pub fn shape_text(face: &Face, text: &str, size: f32, direction: Direction, script: Option<Script>) -> Vec<ShapedGlyph> {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_direction(direction);
+    if let Some(script) = script {
+        buffer.set_script(script);
+    }
+    buffer.guess_segment_properties();
+    let glyphs = rustybuzz::shape(face, &[], buffer);
+    let scale = font_scale(face, size);
+    glyphs.glyph_infos().iter()
+        .zip(glyphs.glyph_positions())
+        .map(|(info, position)| ShapedGlyph {
+            id: info.glyph_id as u16,
+            cluster: info.cluster as usize,
+            advance: Point {x: position.x_advance as f32 * scale, y: -position.y_advance as f32 * scale},
+            offset: Point {x: position.x_offset as f32 * scale, y: -position.y_offset as f32 * scale}
+        })
+        .collect()
+}
+
+/// Line metrics of font at size.
+pub fn line_metrics(face: &Face, size: f32) -> LineMetrics {
+    let scale = font_scale(face, size);
+    LineMetrics {
+        ascent: face.ascender() as f32 * scale,
+        descent: -face.descender() as f32 * scale,
+        height: (face.ascender() - face.descender() + face.line_gap()) as f32 * scale
+    }
+}
+
+/// Underline (by post table) & strikeout (by OS/2 table) metrics, fonts without them get typical proportions of font size.
+pub fn decoration_metrics(face: &Face, size: f32) -> (DecorationMetrics, DecorationMetrics) {
+    let scale = font_scale(face, size);
+    let to_pixels = |metrics: ttf_parser::LineMetrics| DecorationMetrics {position: metrics.position as f32 * scale, thickness: (metrics.thickness as f32 * scale).max(0.0)};
+    let fallback_thickness = size / 14.0;
+    (
+        face.underline_metrics().map_or(DecorationMetrics {position: -size * 0.1, thickness: fallback_thickness}, to_pixels),
+        face.strikeout_metrics().map_or(DecorationMetrics {position: size * 0.3, thickness: fallback_thickness}, to_pixels)
+    )
+}
+
+// Identity of glyph outline: face (by addresses of head & outline tables), glyph, size & synthesis
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+struct GlyphKey {
+    face: (usize, usize),
+    glyph: u16,
+    size: u32,
+    synthesis: (bool, bool)
+}
+impl GlyphKey {
+    fn new(face: &Face, glyph: u16, size: f32, synthesis: Synthesis) -> Self {
+        let table_address = |tags: &[&[u8; 4]]| tags.iter()
+            .find_map(|tag| face.table_data(Tag::from_bytes(tag)))
+            .map_or(0, |data| data.as_ptr() as usize);
+        Self {
+            face: (table_address(&[b"head"]), table_address(&[b"glyf", b"CFF ", b"CFF2"])),
+            glyph,
+            size: size.to_bits(),
+            synthesis: (synthesis.embolden, synthesis.oblique)
+        }
+    }
+}
+
+/// Least recently used glyph outlines (relative to glyph origin). Shareable by threads.
+/// Faces get identified by their data, which has to outlive the cache (like fonts of a renderer).
+pub struct GlyphCache {
+    outlines: Mutex<LruCache<GlyphKey, Arc<Path>>>
+}
+impl GlyphCache {
+    // Cached outline of glyph or created one
+    fn outline(&self, face: &Face, glyph: u16, size: f32, synthesis: Synthesis) -> Arc<Path> {
+        let key = GlyphKey::new(face, glyph, size, synthesis);
+        if let Some(outline) = self.lock().get(&key).cloned() {
+            return outline;
+        }
+        let mut outline = Path::default();
+        face.outline_glyph(GlyphId(glyph), &mut GlyphPathBuilder {
+            path: &mut outline,
+            scale: font_scale(face, size),
+            shear: if synthesis.oblique {OBLIQUE_DEGREES.to_radians().tan()} else {0.0},
+            origin: Point::default(),
+            last_point: Point::default()
+        });
+        if synthesis.embolden {
+            outline = embolden_path(&outline, size * EMBOLDEN_FACTOR * 0.5);
+        }
+        let outline = Arc::new(outline);
+        self.lock().put(key, outline.clone());
+        outline
+    }
+    // Locked outlines (a panicking renderer thread leaves a consistent cache)
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<GlyphKey, Arc<Path>>> {
+        self.outlines.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+impl Default for GlyphCache {
+    fn default() -> Self {
+        Self {
+            outlines: Mutex::new(LruCache::new(GLYPH_CACHE_ENTRIES))
+        }
+    }
+}
+impl Clone for GlyphCache {
+    // Clones start empty
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+impl PartialEq for GlyphCache {
+    // Cached content doesn't change rendering
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+impl fmt::Debug for GlyphCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlyphCache")
+            .field("entries", &self.lock().len())
+            .finish()
+    }
+}
+
+/// Appends outlines of shaped glyphs (cached), starting at baseline origin, to path. Returns pen position after last glyph.
+/// Synthesis slants glyphs around their origin and widens outlines by moving points outwards.
+pub fn glyphs_to_path(face: &Face, glyphs: &[ShapedGlyph], size: f32, synthesis: Synthesis, origin: Point, path: &mut Path, cache: &GlyphCache) -> Point {
+    glyphs.iter().fold(origin, |pen, glyph| {
+        let glyph_origin = pen + glyph.offset;
+        for segment in cache.outline(face, glyph.id, size, synthesis).segments() {
+            match segment {
+                PathSegment::Flat(FlatPathSegment::MoveTo(point)) => path.move_to(*point + glyph_origin),
+                PathSegment::Flat(FlatPathSegment::LineTo(point)) => path.line_to(*point + glyph_origin),
+                PathSegment::CurveTo(control_point1, control_point2, end_point) => path.curve_to(*control_point1 + glyph_origin, *control_point2 + glyph_origin, *end_point + glyph_origin),
+                PathSegment::Flat(FlatPathSegment::Close) => path.close()
+            };
+        }
+        pen + glyph.advance
+    })
+}
+
+/// Sign of enclosed area by control polygons of path segments (positive = clockwise with y-axis down).
+pub fn outline_orientation(segments: &[PathSegment]) -> Coordinate {
+    let cross = |point1: Point, point2: Point| point1.x * point2.y - point1.y * point2.x;
+    let (mut area, mut start, mut current) = (0.0, Point::default(), Point::default());
+    for segment in segments {
+        match segment {
+            PathSegment::Flat(FlatPathSegment::MoveTo(point)) => {
+                area += cross(current, start);
+                start = *point;
+                current = *point;
+            }
+            PathSegment::Flat(FlatPathSegment::LineTo(point)) => {
+                area += cross(current, *point);
+                current = *point;
+            }
+            PathSegment::CurveTo(control_point1, control_point2, point) => {
+                area += cross(current, *control_point1) + cross(*control_point1, *control_point2) + cross(*control_point2, *point);
+                current = *point;
+            }
+            PathSegment::Flat(FlatPathSegment::Close) => {
+                area += cross(current, start);
+                current = start;
+            }
+        }
+    }
+    area += cross(current, start);
+    if area < 0.0 {-1.0} else {1.0}
+}
+
+// Moves outline points (control points included) outwards by strength along their corner bisectors
+fn embolden_path(path: &Path, strength: f32) -> Path {
+    let orientation = outline_orientation(path.segments());
+    let mut points: Vec<Point> = path.segments().iter()
+        .flat_map(|segment| match segment {
+            PathSegment::Flat(FlatPathSegment::MoveTo(point)) | PathSegment::Flat(FlatPathSegment::LineTo(point)) => vec![*point],
+            PathSegment::CurveTo(control_point1, control_point2, end_point) => vec![*control_point1, *control_point2, *end_point],
+            PathSegment::Flat(FlatPathSegment::Close) => vec![]
+        })
+        .collect();
+    // Contours as point ranges
+    let mut contours = vec![];
+    let mut index = 0;
+    for segment in path.segments() {
+        match segment {
+            PathSegment::Flat(FlatPathSegment::MoveTo(_)) => {
+                contours.push(index..index + 1);
+                index += 1;
+            }
+            PathSegment::Flat(FlatPathSegment::LineTo(_)) => index += 1,
+            PathSegment::CurveTo(..) => index += 3,
+            PathSegment::Flat(FlatPathSegment::Close) => {}
+        }
+        if let Some(contour) = contours.last_mut() {
+            contour.end = index;
+        }
+    }
+    let outward = |from: Point, to: Point| {
+        let (x, y) = (to.x - from.x, to.y - from.y);
+        let length = (x * x + y * y).sqrt();
+        if length > 0.0 {Some(Point {x: y / length * orientation, y: -x / length * orientation})} else {None}
+    };
+    for contour in contours {
+        let original = points[contour.clone()].to_vec();
+        // Closing point equal to start shares its shift
+        let count = if original.len() > 1 && original.first() == original.last() {original.len() - 1} else {original.len()};
+        if count < 2 {
+            continue;
+        }
+        for point_index in 0..count {
+            let point = original[point_index];
+            let previous = (1..count).map(|offset| original[(point_index + count - offset) % count]).find(|previous| *previous != point);
+            let next = (1..count).map(|offset| original[(point_index + offset) % count]).find(|next| *next != point);
+            if let (Some(normal_in), Some(normal_out)) = (previous.and_then(|previous| outward(previous, point)), next.and_then(|next| outward(point, next))) {
+                let normal_sum = normal_in + normal_out;
+                let divisor = 1.0 + normal_in.x * normal_out.x + normal_in.y * normal_out.y;
+                // Spikes of very sharp corners get limited
+                let shift = if divisor > 0.25 {normal_sum * (strength / divisor)} else {normal_in * strength};
+                points[contour.start + point_index] = point + shift;
+                if count < original.len() && point_index == 0 {
+                    points[contour.start + count] = point + shift;
+                }
+            }
+        }
+    }
+    // Rebuild segments with moved points
+    let mut points = points.into_iter();
+    let mut next_point = || points.next().unwrap_or_default();
+    Path::new(
+        path.segments().iter()
+            .map(|segment| match segment {
+                PathSegment::Flat(FlatPathSegment::MoveTo(_)) => PathSegment::Flat(FlatPathSegment::MoveTo(next_point())),
+                PathSegment::Flat(FlatPathSegment::LineTo(_)) => PathSegment::Flat(FlatPathSegment::LineTo(next_point())),
+                PathSegment::CurveTo(..) => PathSegment::CurveTo(next_point(), next_point(), next_point()),
+                PathSegment::Flat(FlatPathSegment::Close) => PathSegment::Flat(FlatPathSegment::Close)
+            })
+            .collect()
+    )
+}
+
+// Converts glyph outline from font units (y-axis up) to pixels (y-axis down)
+struct GlyphPathBuilder<'a> {
+    path: &'a mut Path,
+    scale: f32,
+    shear: f32,
+    origin: Point,
+    last_point: Point
+}
+impl GlyphPathBuilder<'_> {
+    fn point(&self, x: f32, y: f32) -> Point {
+        Point {x: self.origin.x + (x + y * self.shear) * self.scale, y: self.origin.y - y * self.scale}
+    }
+}
+impl OutlineBuilder for GlyphPathBuilder<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.last_point = self.point(x, y);
+        self.path.move_to(self.last_point);
+    }
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.last_point = self.point(x, y);
+        self.path.line_to(self.last_point);
+    }
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        // Elevate quadratic to cubic curve
+        let (control_point, end_point) = (self.point(x1, y1), self.point(x, y));
+        self.path.curve_to(
+            self.last_point + (control_point - self.last_point) * (2.0 / 3.0),
+            end_point + (control_point - end_point) * (2.0 / 3.0),
+            end_point
+        );
+        self.last_point = end_point;
+    }
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let end_point = self.point(x, y);
+        self.path.curve_to(self.point(x1, y1), self.point(x2, y2), end_point);
+        self.last_point = end_point;
+    }
+    fn close(&mut self) {
+        self.path.close();
+    }
+}
+
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{shape_text,line_metrics,decoration_metrics,glyphs_to_path,GlyphCache,Synthesis,Face,Direction,Point,Path};
+    use puny2d::vector::path::FlatPath;
+    use crate::text::common::test_font;
+
+    #[test]
+    fn shape_kerning_ligatures() {
+        let data = test_font();
+        let face = Face::from_slice(&data, 0).expect("Test font should be valid!");
+        // Ligatures merge glyphs, kerning moves them closer
+        assert!(shape_text(&face, "ffi", 20.0, Direction::LeftToRight, None).len() < 3);
+        let advance = |text: &str| shape_text(&face, text, 20.0, Direction::LeftToRight, None).iter().map(|glyph| glyph.advance.x).sum::<f32>();
+        assert!(advance("AV") < advance("A") + advance("V"));
+        assert_eq!(shape_text(&face, "", 20.0, Direction::LeftToRight, None), vec![]);
+    }
+
+    #[test]
+    fn glyph_outlines() {
+        let data = test_font();
+        let face = Face::from_slice(&data, 0).expect("Test font should be valid!");
+        let metrics = line_metrics(&face, 20.0);
+        assert!(metrics.ascent > 0.0 && metrics.descent > 0.0 && metrics.height >= metrics.ascent + metrics.descent);
+        let glyphs = shape_text(&face, "I", 20.0, Direction::LeftToRight, None);
+        let mut path = Path::default();
+        let pen = glyphs_to_path(&face, &glyphs, 20.0, Synthesis::default(), Point {x: 5.0, y: metrics.ascent}, &mut path, &GlyphCache::default());
+        assert!(pen.x > 5.0 && pen.y == metrics.ascent);
+        let (min_point, max_point) = FlatPath::from(path).bounding().expect("Glyph should have an outline!");
+        assert!(min_point.x > 5.0 && max_point.x < pen.x);
+        assert!(min_point.y >= 0.0 && (max_point.y - metrics.ascent).abs() < 0.01);
+        // Cached outlines get placed at glyph origin
+        let cache = GlyphCache::default();
+        let (mut first, mut second) = (Path::default(), Path::default());
+        glyphs_to_path(&face, &glyphs, 20.0, Synthesis::default(), Point {x: 0.0, y: 0.0}, &mut first, &cache);
+        glyphs_to_path(&face, &glyphs, 20.0, Synthesis::default(), Point {x: 3.0, y: 0.0}, &mut second, &cache);
+        let (first, second) = (FlatPath::from(first).bounding(), FlatPath::from(second).bounding());
+        assert_eq!(first.map(|(min_point, _)| min_point.x + 3.0), second.map(|(min_point, _)| min_point.x));
+    }
+
+    #[test]
+    fn decoration_by_font() {
+        let data = test_font();
+        let face = Face::from_slice(&data, 0).expect("Test font should be valid!");
+        let (underline, strikeout) = decoration_metrics(&face, 20.0);
+        assert!(underline.position < 0.0 && strikeout.position > 0.0);
+        assert!(underline.thickness > 0.0 && underline.thickness < 5.0 && strikeout.thickness > 0.0);
+    }
+
+    #[test]
+    fn glyph_synthesis() {
+        let data = test_font();
+        let face = Face::from_slice(&data, 0).expect("Test font should be valid!");
+        let glyphs = shape_text(&face, "I", 20.0, Direction::LeftToRight, None);
+        let cache = GlyphCache::default();
+        let outline = |synthesis: Synthesis| {
+            let mut path = Path::default();
+            let pen = glyphs_to_path(&face, &glyphs, 20.0, synthesis, Point {x: 0.0, y: 20.0}, &mut path, &cache);
+            let (min_point, max_point) = FlatPath::from(path).bounding().expect("Glyph should have an outline!");
+            (pen.x, min_point, max_point)
+        };
+        let (regular_advance, regular_min, regular_max) = outline(Synthesis::default());
+        // Emboldening widens stem (advance is up to layout)
+        let (bold_advance, bold_min, bold_max) = outline(Synthesis {embolden: true, oblique: false});
+        assert_eq!(bold_advance, regular_advance);
+        assert!(Synthesis {embolden: true, oblique: false}.advance(20.0) > 0.0);
+        assert!((bold_max.x - bold_min.x) - (regular_max.x - regular_min.x) > 0.5);
+        assert!(bold_min.x < regular_min.x && bold_max.x > regular_max.x);
+        // Obliquing shears top to the right, keeps baseline
+        let (oblique_advance, oblique_min, oblique_max) = outline(Synthesis {embolden: false, oblique: true});
+        assert_eq!(oblique_advance, regular_advance);
+        assert!((oblique_min.x - regular_min.x).abs() < 0.01 && oblique_max.x > regular_max.x + 2.0);
+        assert_eq!((oblique_min.y, oblique_max.y), (regular_min.y, regular_max.y));
+    }
+}
\ No newline at end of file
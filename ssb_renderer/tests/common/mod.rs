@@ -0,0 +1,24 @@
+// Test helpers, shared by unit & integration tests
+
+// System fonts (directory & regular face file) to test with
+const TEST_FONTS: [(&str, &str); 3] = [
+    ("/usr/share/fonts/truetype/dejavu", "DejaVuSans.ttf"),
+    ("/usr/share/fonts/truetype/msttcorefonts", "Arial.ttf"),
+    ("C:/Windows/Fonts", "arial.ttf")
+];
+
+/// Directory of first available test fonts ("DejaVu Sans" or "Arial").
+#[allow(dead_code)]
+pub fn test_font_directory() -> &'static std::path::Path {
+    TEST_FONTS.iter()
+        .map(|(directory, _)| std::path::Path::new(directory))
+        .find(|directory| directory.is_dir())
+        .expect("Font directory required!")
+}
+
+/// Data of first available test font ("DejaVu Sans" or "Arial").
+pub fn test_font() -> Vec<u8> {
+    TEST_FONTS.iter()
+        .find_map(|(directory, file)| std::fs::read(std::path::Path::new(directory).join(file)).ok())
+        .expect("Test font required!")
+}
\ No newline at end of file
@@ -0,0 +1,684 @@
+// Shared helpers
+mod common;
+
+mod rendering_tests {
+    // Imports
+    use std::{
+        convert::TryFrom,
+        io::Cursor,
+        sync::Arc
+    };
+    use ssb_parser::{
+        Ssb,
+        SsbRender,
+        objects::ssb_objects::{FontFace,FontStyle}
+    };
+    use ssb_renderer::{
+        image::{ColorType,ImageView},
+        RenderTrigger,
+        Rect,
+        Overlay,
+        SsbRenderer,
+        YuvImageView,
+        YuvFormat,
+        ColorMatrix
+    };
+    use super::common::test_font;
+
+    // Helpers
+    fn renderer(script: &str) -> SsbRenderer {
+        SsbRenderer::new(
+            Ssb::default().parse_owned(Cursor::new(script))
+            .and_then(SsbRender::try_from)
+            .expect("Script should be valid!")
+        )
+    }
+    fn renderer_with_font(script: &str) -> SsbRenderer {
+        let mut data = Ssb::default().parse_owned(Cursor::new(script))
+            .and_then(SsbRender::try_from)
+            .expect("Script should be valid!");
+        data.fonts.insert(
+            FontFace {family: "Test".to_owned(), style: FontStyle::Regular},
+            test_font()
+        );
+        SsbRenderer::new(data)
+    }
+    fn renderer_with_texture(script: &str) -> SsbRenderer {
+        let mut data = Ssb::default().parse_owned(Cursor::new(script))
+            .and_then(SsbRender::try_from)
+            .expect("Script should be valid!");
+        // Red & blue pixel
+        let mut png_data = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut png_data, 2, 1);
+            encoder.set_color(png::ColorType::RGB);
+            encoder.write_header().and_then(|mut writer| writer.write_image_data(&[255, 0, 0, 0, 0, 255]))
+                .expect("PNG encoding shouldn't fail!");
+        }
+        data.textures.insert("Test".to_owned(), png_data);
+        SsbRenderer::new(data)
+    }
+
+    #[test]
+    fn test_render_shape() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;color=FF8000]m 2 2 l 6 2 6 6 2 6 c");
+        let mut data = vec![0u8; 8 * 8 * 3];
+        renderer.render(
+            ImageView::new(8, 8, 8 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(500)
+        ).expect("Image rendering mustn't fail!");
+        assert_eq!(&data[(3 * 8 + 3) * 3..(3 * 8 + 4) * 3], &[255, 128, 0]);
+        assert_eq!(&data[(7 * 8 + 7) * 3..(7 * 8 + 8) * 3], &[0, 0, 0]);
+    }
+
+    #[test]
+    fn test_render_shape_yuv() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;color=FF0000]m 2 2 l 6 2 6 6 2 6");
+        // Black 10-bit frame in limited range
+        let to_bytes = |samples: Vec<u16>| samples.into_iter().flat_map(u16::to_ne_bytes).collect::<Vec<_>>();
+        let (mut luma, mut cb, mut cr) = (to_bytes(vec![64; 8 * 8]), to_bytes(vec![512; 4 * 4]), to_bytes(vec![512; 4 * 4]));
+        renderer.render_yuv(
+            YuvImageView::new(
+                8, 8,
+                YuvFormat::by_name("YUV420P10", ColorMatrix::BT709, false).expect("YUV format should be valid!"),
+                [8 * 2, 4 * 2, 4 * 2],
+                vec![&mut luma, &mut cb, &mut cr]
+            ).expect("YuvImageView must've valid dimensions!"),
+            RenderTrigger::Time(500)
+        ).expect("Image rendering mustn't fail!");
+        let sample = |data: &[u8], index: usize| u16::from_ne_bytes([data[index * 2], data[index * 2 + 1]]);
+        // Red by BT.709
+        assert_eq!((sample(&luma, 3 * 8 + 3), sample(&luma, 7 * 8 + 7)), (250, 64));
+        assert_eq!((sample(&cb, 4 + 1), sample(&cr, 4 + 1)), (409, 960));
+        assert_eq!((sample(&cb, 3 * 4 + 3), sample(&cr, 3 * 4 + 3)), (512, 512));
+    }
+
+    #[test]
+    fn test_render_blend_yuv() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;color=808080;blend=multiply]m 0 0 l 4 0 4 4 0 4[color=FFFFFF;blend=invert]m 4 0 l 8 0 8 4 4 4");
+        // Grey 8-bit frame in full range
+        let (mut luma, mut cb, mut cr) = (vec![128u8; 8 * 8], vec![128u8; 8 * 8], vec![128u8; 8 * 8]);
+        renderer.render_yuv(
+            YuvImageView::new(
+                8, 8,
+                YuvFormat::by_name("YUV444P8", ColorMatrix::BT601, true).expect("YUV format should be valid!"),
+                [8, 8, 8],
+                vec![&mut luma, &mut cb, &mut cr]
+            ).expect("YuvImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        // Blending mixes with image colors
+        assert_eq!((luma[2 * 8 + 2], luma[2 * 8 + 6], luma[6 * 8 + 2]), (64, 127, 128));
+        assert!(cb.iter().chain(&cr).all(|sample| *sample == 128));
+    }
+
+    #[test]
+    fn test_render_shape_curves() {
+        let renderer = renderer("#EVENTS\n'circle'|||[mode=shape]m 8 0 a 8 8 360 m 0 16 b 0 12 4 12 4 16");
+        let mut data = vec![0u8; 16 * 16];
+        renderer.render(
+            ImageView::new(16, 16, 16, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Id("circle")
+        ).expect("Image rendering mustn't fail!");
+        assert_eq!(data[8 * 16 + 8], 255);
+        assert_eq!(data[0], 0);
+        assert!((1..255).contains(&data[2 * 16 + 2]), "Anti-aliased edge expected: {}", data[2 * 16 + 2]);
+        assert!(data[15 * 16 + 2] > 0, "Curve segment expected!");
+    }
+
+    #[test]
+    fn test_render_shape_border() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;border=2,1;join=miter;bordercolor=FF0000]m 4 4 l 12 4 12 12 4 12 c");
+        let mut data = vec![0u8; 16 * 16 * 3];
+        renderer.render(
+            ImageView::new(16, 16, 16 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let pixel = |x: usize, y: usize| &data[(y * 16 + x) * 3..(y * 16 + x + 1) * 3];
+        // Fill over border inside, border outside with horizontal & vertical widths
+        assert_eq!((pixel(8, 8), pixel(4, 8)), (&[255, 255, 255][..], &[255, 255, 255][..]));
+        assert_eq!((pixel(2, 8), pixel(8, 3), pixel(2, 3)), (&[255, 0, 0][..], &[255, 0, 0][..], &[255, 0, 0][..]));
+        assert_eq!((pixel(1, 8), pixel(8, 2)), (&[0, 0, 0][..], &[0, 0, 0][..]));
+    }
+
+    #[test]
+    fn test_render_shape_border_translucent() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;border=2;alpha=80;bordercolor=FF0000]m 4 4 l 12 4 12 12 4 12 c");
+        let mut data = vec![0u8; 16 * 16 * 3];
+        renderer.render(
+            ImageView::new(16, 16, 16 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let pixel = |x: usize, y: usize| &data[(y * 16 + x) * 3..(y * 16 + x + 1) * 3];
+        // Border band inside outline doesn't show through fill
+        assert_eq!(pixel(8, 8), pixel(5, 8));
+        assert!(pixel(5, 8)[1] > 0 && pixel(5, 8)[0] == pixel(5, 8)[1], "Only translucent fill expected: {:?}", pixel(5, 8));
+        assert_eq!(pixel(3, 8), &[255, 0, 0][..]);
+    }
+
+    #[test]
+    fn test_render_shape_gradient() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;color=000000,FFFFFF;border=1;bordercolor=FF0000;borderalpha=FF,00]m 2 2 l 14 2 14 14 2 14 c");
+        let mut data = vec![0u8; 16 * 16 * 3];
+        renderer.render(
+            ImageView::new(16, 16, 16 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let pixel = |x: usize, y: usize| &data[(y * 16 + x) * 3..(y * 16 + x + 1) * 3];
+        // Fill from black to white, border from opaque to transparent over own bounding box
+        assert!(pixel(3, 8)[0] <= 32 && pixel(12, 8)[0] >= 223, "Horizontal gradient expected: {:?} -> {:?}", pixel(3, 8), pixel(12, 8));
+        assert!(pixel(7, 8)[0] < pixel(8, 8)[0]);
+        assert!(pixel(1, 8)[0] > 192 && pixel(14, 8)[0] < 64, "Border alpha gradient expected: {:?} -> {:?}", pixel(1, 8), pixel(14, 8));
+    }
+
+    #[test]
+    fn test_render_shape_texture() {
+        let renderer = renderer_with_texture("#EVENTS\n0-1.|||[mode=shape;texture=Test;texfill=0,0,0.5,1,repeat]m 0 0 l 16 0 16 4 0 4[texfill=0,0,0.5,1,mirror]m 0 4 l 16 4 16 8 0 8[texfill=0,0,0.5,1,pad]m 0 8 l 16 8 16 12 0 12");
+        let mut data = vec![0u8; 16 * 12 * 3];
+        renderer.render(
+            ImageView::new(16, 12, 16 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let pixel = |x: usize, y: usize| &data[(y * 16 + x) * 3..(y * 16 + x + 1) * 3];
+        let (reddish, bluish) = (|pixel: &[u8]| pixel[0] > 192 && pixel[2] < 64, |pixel: &[u8]| pixel[2] > 192 && pixel[0] < 64);
+        // Texture stretched over left half, continued by wrapping
+        assert!(reddish(pixel(1, 1)) && bluish(pixel(5, 1)) && reddish(pixel(9, 1)) && bluish(pixel(13, 1)), "Repeated texture expected!");
+        assert!(reddish(pixel(1, 5)) && bluish(pixel(5, 5)) && bluish(pixel(9, 5)) && reddish(pixel(13, 5)), "Mirrored texture expected!");
+        assert!(reddish(pixel(1, 9)) && bluish(pixel(5, 9)) && pixel(12, 9) == [0, 0, 0], "Padded texture expected!");
+        // Missing texture
+        assert!(renderer_with_texture("#EVENTS\n0-1.|||[mode=shape;texture=Missing]m 0 0 l 8 0 8 8 0 8").render(
+            ImageView::new(16, 12, 16 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).is_err());
+    }
+
+    #[test]
+    fn test_render_texture_invalid() {
+        let mut data = Ssb::default().parse_owned(Cursor::new("#EVENTS\n0-1.|||[mode=shape;texture=Broken]m 0 0 l 8 0 8 8 0 8\n'clear'|||[texture=Missing;mask-clear]"))
+            .and_then(SsbRender::try_from)
+            .expect("Script should be valid!");
+        data.textures.insert("Broken".to_owned(), vec![0u8; 16]);
+        let renderer = SsbRenderer::new(data);
+        let render = |trigger| {
+            let mut data = vec![0u8; 8 * 8 * 3];
+            renderer.render(
+                ImageView::new(8, 8, 8 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+                trigger
+            ).map(|_| ()).map_err(|err| err.to_string())
+        };
+        // Undecodable texture reported on use, none needed to clear mask
+        assert!(matches!(render(RenderTrigger::Time(0)), Err(err) if err.starts_with("Texture 'Broken' couldn't be decoded!")));
+        assert!(render(RenderTrigger::Id("clear")).is_ok());
+    }
+
+    #[test]
+    fn test_render_shape_blur() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;blur-h=2]m 8 0 l 24 0 24 4 8 4[blur=0,2]m 8 12 l 24 12 24 16 8 16");
+        let mut data = vec![0u8; 32 * 16];
+        renderer.render(
+            ImageView::new(32, 16, 32, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let pixel = |x: usize, y: usize| data[y * 32 + x];
+        // Horizontal blur softens left & right edges only
+        assert_eq!(pixel(16, 2), 255);
+        assert!((1..128).contains(&pixel(6, 2)) && (128..255).contains(&pixel(8, 2)), "Soft horizontal edge expected: {} {}", pixel(6, 2), pixel(8, 2));
+        assert_eq!(pixel(16, 5), 0);
+        // Vertical blur softens top edge only
+        assert!((1..128).contains(&pixel(16, 10)) && pixel(6, 14) == 0, "Soft vertical edge expected: {} {}", pixel(16, 10), pixel(6, 14));
+    }
+
+    #[test]
+    fn test_render_shape_blend() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;color=808080]m 0 0 l 16 0 16 8 0 8[color=FF0000;blend=multiply]m 0 0 l 4 0 4 8 0 8[blend=add]m 4 0 l 8 0 8 8 4 8[blend=invert]m 8 0 l 12 0 12 8 8 8[color=FFFFFF;blend=difference]m 12 0 l 16 0 16 8 12 8");
+        let mut data = vec![0u8; 16 * 8 * 3];
+        renderer.render(
+            ImageView::new(16, 8, 16 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let pixel = |x: usize, y: usize| &data[(y * 16 + x) * 3..(y * 16 + x + 1) * 3];
+        assert_eq!(pixel(2, 4), &[128, 0, 0]);
+        assert_eq!(pixel(6, 4), &[255, 128, 128]);
+        assert_eq!(pixel(10, 4), &[127, 127, 127]);
+        assert_eq!(pixel(14, 4), &[127, 127, 127]);
+    }
+
+    #[test]
+    fn test_render_shape_mask() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;target=mask]m 0 0 l 8 0 8 12 0 12[target=frame]m 0 0 l 16 0 16 4 0 4[mask-mode=invert]m 0 4 l 16 4 16 8 0 8[mask-clear]m 0 8 l 16 8 16 12 0 12");
+        let mut data = vec![0u8; 16 * 12];
+        renderer.render(
+            ImageView::new(16, 12, 16, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let pixel = |x: usize, y: usize| data[y * 16 + x];
+        // Mask itself invisible, clipping inside or outside of it until cleared
+        assert_eq!((pixel(4, 2), pixel(12, 2)), (255, 0));
+        assert_eq!((pixel(4, 6), pixel(12, 6)), (0, 255));
+        assert_eq!((pixel(4, 10), pixel(12, 10)), (255, 255));
+    }
+
+    #[test]
+    fn test_render_shape_animate() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;color=000000;animate=0,-500,t^2,[color=C8C8C8;translate-x=8]]m 0 0 l 8 0 8 8 0 8\n'id'|||[mode=shape;animate=[alpha=00]]m 0 0 l 8 0 8 8 0 8");
+        let render = |trigger| {
+            let mut data = vec![0u8; 16 * 8];
+            renderer.render(
+                ImageView::new(16, 8, 16, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+                trigger
+            ).expect("Image rendering mustn't fail!");
+            data
+        };
+        // Eased halfway of animation range, finished after
+        let data = render(RenderTrigger::Time(250));
+        assert_eq!((data[4 * 16 + 1], data[4 * 16 + 2], data[4 * 16 + 9]), (0, 50, 50));
+        let data = render(RenderTrigger::Time(750));
+        assert_eq!((data[4 * 16 + 7], data[4 * 16 + 8]), (0, 200));
+        // Without time animation counts as finished
+        assert!(render(RenderTrigger::Id("id")).iter().all(|sample| *sample == 0));
+    }
+
+    #[test]
+    fn test_render_shape_animate_position() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;animate=[position=8,0]]m 0 0 l 4 0 4 4 0 4\n0-1.|||[mode=shape;animate=0,1000,t+,[position=8,4]]m 0 0 l 4 0 4 4 0 4");
+        let mut data = vec![0u8; 16 * 8];
+        renderer.render(
+            ImageView::new(16, 8, 16, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(500)
+        ).expect("Image rendering mustn't fail!");
+        // Halfway from default anchor, invalid formula eases linearly
+        assert_eq!((data[2 * 16 + 3], data[2 * 16 + 5], data[2 * 16 + 9]), (0, 255, 0));
+        assert_eq!((data[4 * 16 + 3], data[5 * 16 + 5], data[7 * 16 + 8]), (0, 255, 0));
+    }
+
+    #[test]
+    fn test_render_shape_karaoke() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;kcolor=FF0000;k=200]m 0 0 l 4 0 4 4 0 4[k=300]m 4 0 l 8 0 8 4 4 4[kset=100;k=100]m 8 0 l 12 0 12 4 8 4");
+        let render = |time| {
+            let mut data = vec![0u8; 12 * 4 * 3];
+            renderer.render(
+                ImageView::new(12, 4, 12 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(time)
+            ).expect("Image rendering mustn't fail!");
+            [data[(2 * 12 + 2) * 3 + 1], data[(2 * 12 + 6) * 3 + 1], data[(2 * 12 + 10) * 3 + 1]]
+        };
+        // Green channel shows which syllables are still white
+        assert_eq!(render(0), [0, 255, 255]);
+        assert_eq!(render(150), [0, 255, 0]);
+        assert_eq!(render(200), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_render_points() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=points;border=2]4 4[cap=square;border=1,0]12 4[cap=butt]4 12");
+        let mut data = vec![0u8; 16 * 16 * 3];
+        renderer.render(
+            ImageView::new(16, 16, 16 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let pixel = |x: usize, y: usize| data[(y * 16 + x) * 3];
+        assert_eq!((pixel(3, 3), pixel(4, 4), pixel(1, 1)), (255, 255, 0));
+        assert_eq!((pixel(11, 3), pixel(12, 4), pixel(13, 4), pixel(11, 2)), (128, 128, 0, 0));
+        assert_eq!((pixel(3, 11), pixel(4, 12)), (0, 0));
+    }
+
+    #[test]
+    fn test_render_points_border() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=points;border=4;color=FFFFFF;bordercolor=FF0000]16 8");
+        let mut data = vec![128u8; 32 * 16 * 3];
+        renderer.render(
+            ImageView::new(32, 16, 32 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        // Dots sized by border without outline
+        let pixel = |x: usize| &data[(8 * 32 + x) * 3..(8 * 32 + x + 1) * 3];
+        assert_eq!((pixel(13), pixel(18)), (&[255, 255, 255][..], &[255, 255, 255][..]));
+        assert!([pixel(12), pixel(19)].iter().all(|edge| edge[0] > 128 && edge[0] == edge[1] && edge[1] == edge[2]), "Dot edge shouldn't get border color!");
+        assert!((0..32).filter(|x| !(12..20).contains(x)).all(|x| pixel(x) == [128, 128, 128]), "Dot should only cover its radius!");
+    }
+
+    #[test]
+    fn test_render_text() {
+        let renderer = renderer_with_font("#EVENTS\n0-1.|||[font=Test;size=20;color=00FF00;alignment=7]I\\nI");
+        let mut data = vec![0u8; 16 * 48 * 3];
+        renderer.render(
+            ImageView::new(16, 48, 16 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let row_coverage = |y: usize| data[y * 16 * 3..(y + 1) * 16 * 3].chunks_exact(3).map(|pixel| pixel[1] as u32).sum::<u32>();
+        assert!(row_coverage(10) > 0 && row_coverage(33) > 0, "Text lines expected!");
+        assert!(data.chunks_exact(3).all(|pixel| pixel[0] == 0 && pixel[2] == 0));
+    }
+
+    #[test]
+    fn test_render_text_decorations() {
+        let render = |tags: &str| {
+            let renderer = renderer_with_font(&format!("#EVENTS\n0-1.|||[font=Test;size=40;alignment=7;{}]oo", tags));
+            let mut data = vec![0u8; 64 * 64];
+            renderer.render(
+                ImageView::new(64, 64, 64, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(0)
+            ).expect("Image rendering mustn't fail!");
+            data
+        };
+        let rows = |data: &[u8]| data.chunks_exact(64).map(|row| row.iter().filter(|sample| **sample > 128).count()).collect::<Vec<_>>();
+        let (plain, underlined, struck) = (rows(&render("")), rows(&render("underline=y")), rows(&render("strikeout=y")));
+        // Underline below glyphs
+        let last_row = |rows: &[usize]| rows.iter().rposition(|count| *count > 0).expect("Text expected!");
+        assert!(last_row(&underlined) > last_row(&plain));
+        // Strikeout crosses glyphs as one solid line
+        let strikeout_row = (0..64).max_by_key(|y| struck[*y] as i64 - plain[*y] as i64).expect("Rows exist!");
+        assert!(struck[strikeout_row] > plain[strikeout_row]);
+        let data = render("strikeout=y");
+        let row = &data[strikeout_row * 64..(strikeout_row + 1) * 64];
+        let (first, last) = (row.iter().position(|sample| *sample > 128).unwrap_or(0), row.iter().rposition(|sample| *sample > 128).unwrap_or(0));
+        assert!(row[first..=last].iter().all(|sample| *sample > 128), "Solid strikeout expected: {:?}", row);
+    }
+
+    #[test]
+    fn test_render_text_synthesis() {
+        let render = |tags: &str| {
+            let renderer = renderer_with_font(&format!("#EVENTS\n0-1.|||[font=Test;size=40;alignment=7;{}]I", tags));
+            let mut data = vec![0u8; 64 * 64];
+            renderer.render(
+                ImageView::new(64, 64, 64, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(0)
+            ).expect("Only regular font should be enough!");
+            data
+        };
+        let columns = |data: &[u8], rows: std::ops::Range<usize>| (0..64).filter(|x| rows.clone().any(|y| data[y * 64 + x] > 128)).collect::<Vec<_>>();
+        let (regular, bold, italic) = (render(""), render("bold=y"), render("italic=y"));
+        // Emboldened stem is wider
+        assert!(columns(&bold, 0..64).len() > columns(&regular, 0..64).len());
+        // Obliqued stem leans right
+        let (top, bottom) = (columns(&italic, 8..12), columns(&italic, 26..30));
+        assert!(top.first() > bottom.first(), "Slanted stem expected: {:?} / {:?}", top, bottom);
+    }
+
+    #[test]
+    fn test_render_text_vertical() {
+        let renderer = renderer_with_font("#EVENTS\n0-1.|||[font=Test;size=20;direction=ttb;position=48,0;alignment=0,0]II\\nI");
+        let mut data = vec![0u8; 48 * 48];
+        renderer.render(
+            ImageView::new(48, 48, 48, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        // Columns from right to left, glyphs from top to bottom
+        let coverage = |x: std::ops::Range<usize>, y: std::ops::Range<usize>| y.flat_map(|y| x.clone().map(move |x| y * 48 + x)).map(|index| data[index] as u32).sum::<u32>();
+        assert!(coverage(26..48, 0..20) > 0 && coverage(26..48, 24..44) > 0, "Column of two glyphs expected!");
+        assert!(coverage(0..24, 0..20) > 0 && coverage(0..24, 24..48) == 0, "Column of one glyph expected!");
+    }
+
+    #[test]
+    fn test_render_text_wrap() {
+        for (wrap_style, wrapped) in &[("space", true), ("character", true), ("nowrap", false)] {
+            let renderer = renderer_with_font(&format!("#EVENTS\n0-1.|||[font=Test;size=20;alignment=7;margin-right=30;wrap-style={}]HHH HHH", wrap_style));
+            let mut data = vec![0u8; 80 * 48];
+            renderer.render(
+                ImageView::new(80, 48, 80, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(0)
+            ).expect("Image rendering mustn't fail!");
+            assert_eq!(data[33 * 80..34 * 80].iter().any(|sample| *sample > 0), *wrapped, "Wrap style: {}", wrap_style);
+        }
+    }
+
+    #[test]
+    fn test_render_text_alignment() {
+        let render = |alignment: &str| {
+            let renderer = renderer_with_font(&format!("#EVENTS\n0-1.|||[font=Test;size=20;margin=4;{}]I", alignment));
+            let mut data = vec![0u8; 64 * 64];
+            renderer.render(
+                ImageView::new(64, 64, 64, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(0)
+            ).expect("Image rendering mustn't fail!");
+            let coverage = |x: std::ops::Range<usize>, y: std::ops::Range<usize>| y.flat_map(|y| x.clone().map(move |x| y * 64 + x)).map(|index| data[index] as u32).sum::<u32>();
+            (coverage(0..32, 0..32), coverage(32..64, 0..32), coverage(0..32, 32..64), coverage(32..64, 32..64))
+        };
+        assert!(matches!(render("alignment=7"), (top_left, 0, 0, 0) if top_left > 0));
+        assert!(matches!(render("alignment=3"), (0, 0, 0, bottom_right) if bottom_right > 0));
+        assert!(matches!(render("alignment=9;position=30,40"), (0, 0, bottom_left, 0) if bottom_left > 0));
+        assert!(matches!(render("alignment=0,0;position=40,40"), (0, 0, 0, bottom_right) if bottom_right > 0));
+    }
+
+    #[test]
+    fn test_render_target_scale() {
+        let renderer = renderer("#TARGET\nWidth: 32\nHeight: 16\n#EVENTS\n0-1.|||[mode=shape]m 0 0 l 16 0 16 8 0 8");
+        let mut data = vec![0u8; 64 * 32 * 3];
+        renderer.render(
+            ImageView::new(64, 32, 64 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let pixel = |x: usize, y: usize| data[(y * 64 + x) * 3];
+        assert_eq!((pixel(31, 15), pixel(32, 15), pixel(31, 16)), (255, 0, 0));
+    }
+
+    #[test]
+    fn test_render_shape_3d() {
+        for (view, corner) in &[("perspective", 0), ("orthogonal", 255)] {
+            let renderer = renderer(&format!("#TARGET\nWidth: 32\nHeight: 32\nDepth: 32\nView: {}\n#EVENTS\n0-1.|||[mode=shape;translate-z=32]m 0 0 l 32 0 32 32 0 32", view));
+            let mut data = vec![0u8; 32 * 32 * 3];
+            renderer.render(
+                ImageView::new(32, 32, 32 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(0)
+            ).expect("Image rendering mustn't fail!");
+            let pixel = |x: usize, y: usize| data[(y * 32 + x) * 3];
+            assert_eq!((pixel(4, 4), pixel(12, 12)), (*corner, 255), "View: {}", view);
+        }
+        // Rotation around y-axis in perspective shrinks farther (left) side
+        let renderer = renderer("#TARGET\nWidth: 32\nHeight: 32\nDepth: 32\n#EVENTS\n0-1.|||[mode=shape;position=16,16;rotate-y=60]m -16 -8 l 16 -8 16 8 -16 8");
+        let mut data = vec![0u8; 32 * 32 * 3];
+        renderer.render(
+            ImageView::new(32, 32, 32 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let column_coverage = |x: usize| (0..32).map(|y| data[(y * 32 + x) * 3] as u32).sum::<u32>();
+        assert!(column_coverage(11) < column_coverage(20), "Perspective expected: {} >= {}", column_coverage(11), column_coverage(20));
+    }
+
+    #[test]
+    fn test_render_text_missing_font() {
+        let render = |renderer: SsbRenderer| {
+            let mut data = vec![0u8; 32 * 16 * 3];
+            renderer.render(
+                ImageView::new(32, 16, 32 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(0)
+            ).expect("Missing font mustn't fail rendering!");
+            data
+        };
+        // Text without any font gets skipped, rest of event drawn
+        let data = render(renderer("#EVENTS\n0-1.|||[font=Missing;alignment=7]Text[mode=shape]m 0 12 l 4 12 4 16 0 16"));
+        assert_eq!(data.iter().filter(|sample| **sample > 0).count(), 4 * 4 * 3);
+        // Missing family replaced by available one
+        assert_eq!(
+            render(renderer_with_font("#EVENTS\n0-1.|||[font=Missing;size=12;alignment=7]Text")),
+            render(renderer_with_font("#EVENTS\n0-1.|||[font=Test;size=12;alignment=7]Text"))
+        );
+    }
+
+    #[test]
+    fn test_render_threads_tiles() {
+        // Blurred edge crosses tiles
+        let renderer = Arc::new(renderer("#EVENTS\n0-1.|||[mode=shape;blur=0,4]m 0 0 l 8 0 8 256 0 256"));
+        let pool = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(4).build().expect("Thread pool should be creatable!"));
+        // Renderer shared by threads
+        let threads = (0..4)
+            .map(|_| {
+                let (renderer, pool) = (renderer.clone(), pool.clone());
+                std::thread::spawn(move || pool.install(|| {
+                    let mut data = vec![0u8; 8 * 300];
+                    renderer.render(
+                        ImageView::new(8, 300, 8, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+                        RenderTrigger::Time(0)
+                    ).expect("Image rendering mustn't fail!");
+                    data.chunks_exact(8).map(|row| row[4]).collect::<Vec<_>>()
+                }))
+            })
+            .collect::<Vec<_>>();
+        let columns = threads.into_iter().map(|thread| thread.join().expect("Rendering thread mustn't panic!")).collect::<Vec<_>>();
+        assert!(columns.windows(2).all(|pair| pair[0] == pair[1]), "Same rendering expected!");
+        let column = &columns[0];
+        assert!(column[12..240].iter().all(|sample| *sample == 255), "Tile borders shouldn't be visible!");
+        assert!(column[240..280].windows(2).all(|pair| pair[0] >= pair[1]) && column[279] == 0);
+    }
+
+    #[test]
+    fn test_render_mask_tiles() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;target=mask]m 0 0 l 16 0 16 8 0 8[target=frame]m 0 0 l 16 0 16 1024 0 1024");
+        // Tiles beside mask geometry get clipped too
+        let column = rayon::ThreadPoolBuilder::new().num_threads(4).build().expect("Thread pool should be creatable!").install(|| {
+            let mut data = vec![0u8; 16 * 1024];
+            renderer.render(
+                ImageView::new(16, 1024, 16, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(0)
+            ).expect("Image rendering mustn't fail!");
+            data.chunks_exact(16).map(|row| row[8]).collect::<Vec<_>>()
+        });
+        assert!(column[..8].iter().all(|sample| *sample == 255));
+        assert!(column[8..].iter().all(|sample| *sample == 0), "Frame outside mask should be clipped!");
+    }
+
+    #[test]
+    fn test_render_mask_beside_image() {
+        let render = |mask_mode: &str| {
+            let renderer = renderer_with_font(&format!("#EVENTS\n0-1.|||[mode=shape;target=mask;mask-mode={}]m 100 100 l 108 100 108 108 100 108[mode=text;target=frame;font=Test;size=20;alignment=7]I", mask_mode));
+            let mut data = vec![0u8; 16 * 24];
+            renderer.render(
+                ImageView::new(16, 24, 16, ColorType::R8G8B8, vec![&mut data.clone(), &mut data.clone(), &mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(0)
+            ).expect("Image rendering mustn't fail!");
+            data.iter().map(|sample| *sample as u32).sum::<u32>()
+        };
+        // Mask outside of image still clips everything, inverted nothing
+        assert_eq!(render("normal"), 0, "Frame text should be hidden by empty mask!");
+        assert!(render("invert") > 0, "Frame text should be shown by inverted empty mask!");
+    }
+
+    #[test]
+    fn test_render_cache() {
+        let mut renderer = renderer("#EVENTS\n0-1.|||[mode=shape;border=1;blur=1]m 2 2 l 10 2 10 10 2 10\n0-1.|||[mode=shape;animate=[translate-x=8]]m 0 12 l 4 12 4 16 0 16");
+        let render = |renderer: &SsbRenderer, time| {
+            let mut data = vec![0u8; 16 * 16 * 3];
+            renderer.render(
+                ImageView::new(16, 16, 16 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(time)
+            ).expect("Image rendering mustn't fail!");
+            data
+        };
+        // Static geometries (border & fill) get reused, moving one by whole pixels too
+        let first = render(&renderer, 0);
+        let stats = renderer.cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (0, 3, 3));
+        assert_eq!(render(&renderer, 500)[..12 * 16 * 3], first[..12 * 16 * 3]);
+        let stats = renderer.cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (3, 3, 3));
+        // Disabled cache renders the same
+        renderer.set_cache_limits(0, 0);
+        assert_eq!(render(&renderer, 0), first);
+        assert_eq!(renderer.cache_stats().entries, 0);
+    }
+
+    #[test]
+    fn test_render_cache_translated() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;blur=0.5]m 1.5 1.5 l 6 2 5 6.5\n0-1.|||[mode=shape;blur=0.5;position=12,9]m 1.5 1.5 l 6 2 5 6.5");
+        let mut data = vec![0u8; 24 * 24 * 3];
+        renderer.render(
+            ImageView::new(24, 24, 24 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        // Translated copy reuses mask and looks the same
+        let stats = renderer.cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+        for y in 0..12 {
+            for x in 0..12 {
+                let (source, target) = ((y * 24 + x) * 3, ((y + 9) * 24 + x + 12) * 3);
+                assert_eq!(data[source..source + 3], data[target..target + 3], "Pixel {}x{} differs!", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_cache_threads() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;blur=1]m 2 2 l 14 2 14 1020 2 1020");
+        let render = |threads| rayon::ThreadPoolBuilder::new().num_threads(threads).build().expect("Thread pool should be creatable!").install(|| {
+            let mut data = vec![0u8; 16 * 1024 * 3];
+            renderer.render(
+                ImageView::new(16, 1024, 16 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+                RenderTrigger::Time(0)
+            ).expect("Image rendering mustn't fail!");
+            data
+        });
+        // Mask of whole geometry gets shared by tiles, whatever their count
+        let single = render(1);
+        assert_eq!(renderer.cache_stats().entries, 1);
+        assert_eq!(render(4), single);
+        let stats = renderer.cache_stats();
+        assert_eq!((stats.misses, stats.entries), (1, 1));
+    }
+
+    #[test]
+    fn test_render_bounds() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape]m 2 2 l 6 2 6 6 2 6\n0-1.|||[mode=shape;blur=1]m 20 20 l 24 20 24 24 20 24\n0-1.|||[mode=shape;target=mask]m 0 24 l 8 24 8 32 0 32\n2.-3.|||[mode=shape]m 4 4 l 8 4 8 8 4 8 m 6 6 l 10 6 10 10 6 10\n'id'|||[mode=shape]m 30 30 l 40 30 40 40 30 40");
+        // Rectangles of rendering & query match, touched pixels lie inside
+        let mut data = vec![0u8; 32 * 32 * 3];
+        let (_, rects) = renderer.render_region(
+            ImageView::new(32, 32, 32 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        assert_eq!(renderer.bounds(32, 32, RenderTrigger::Time(0)).ok().as_ref(), Some(&rects));
+        assert_eq!(rects.len(), 2);
+        assert_eq!(rects[0], Rect {x: 2, y: 2, width: 4, height: 4});
+        assert!(rects[1].x < 20 && rects[1].x + rects[1].width > 24 && rects[1].y + rects[1].height <= 32);
+        for (index, pixel) in data.chunks_exact(3).enumerate() {
+            let (x, y) = ((index % 32) as u16, (index / 32) as u16);
+            if pixel.iter().any(|sample| *sample != 0) {
+                assert!(rects.iter().any(|rect| (rect.x..rect.x + rect.width).contains(&x) && (rect.y..rect.y + rect.height).contains(&y)), "Pixel {}x{} outside!", x, y);
+            }
+        }
+        // Overlapping rectangles merged, clipped by image
+        assert_eq!(renderer.bounds(32, 32, RenderTrigger::Time(2000)).ok(), Some(vec![Rect {x: 4, y: 4, width: 6, height: 6}]));
+        assert_eq!(renderer.bounds(32, 32, RenderTrigger::Id("id")).ok(), Some(vec![Rect {x: 30, y: 30, width: 2, height: 2}]));
+        // Nothing to do without events
+        assert!(renderer.is_active(RenderTrigger::Time(500)));
+        assert!(!renderer.is_active(RenderTrigger::Time(1500)));
+        assert!(!renderer.is_active(RenderTrigger::Id("other")));
+        assert_eq!(renderer.bounds(32, 32, RenderTrigger::Time(1500)).ok(), Some(vec![]));
+    }
+
+    #[test]
+    fn test_render_overlays() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape;color=FF8000;alpha=80]m 2 2 l 6 2 6 6 2 6\n0-1.|||[mode=shape;color=00FF00;blur=1]m 20 20 l 24 20 24 24 20 24\n0-1.|||[mode=shape;target=mask]m 28 0 l 32 0 32 4 28 4[target=frame]m 10 10 l 14 10 14 14 10 14");
+        let overlays = renderer.render_overlays(32, 32, RenderTrigger::Time(0)).expect("Overlay rendering mustn't fail!");
+        assert_eq!(overlays.len(), 3);
+        assert_eq!(&overlays[0].data[..4], &[128, 64, 0, 128]);
+        // Overlays only as large as their rectangles, masks beside them clip too
+        assert_eq!((overlays[2].x, overlays[2].y, overlays[2].width, overlays[2].height), (10, 10, 4, 4));
+        assert!(overlays[2].data.iter().all(|sample| *sample == 0));
+        // Premultiplied overlays equal rendering on black image
+        let mut data = vec![0u8; 32 * 32 * 3];
+        renderer.render(
+            ImageView::new(32, 32, 32 * 3, ColorType::RGB24, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(0)
+        ).expect("Image rendering mustn't fail!");
+        let mut overlaid = vec![0u8; 32 * 32 * 3];
+        for Overlay {x, y, width, height, data} in &overlays {
+            assert_eq!(data.len(), *width as usize * *height as usize * 4);
+            for (index, pixel) in data.chunks_exact(4).enumerate() {
+                let offset = ((*y as usize + index / *width as usize) * 32 + *x as usize + index % *width as usize) * 3;
+                overlaid[offset..offset + 3].copy_from_slice(&pixel[..3]);
+            }
+        }
+        assert!(overlaid.iter().zip(&data).all(|(overlaid, rendered)| (*overlaid as i16 - *rendered as i16).abs() <= 1));
+        assert_eq!(renderer.render_overlays(32, 32, RenderTrigger::Time(1000)).ok(), Some(vec![]));
+    }
+
+    #[test]
+    fn test_render_untriggered() {
+        let renderer = renderer("#EVENTS\n0-1.|||[mode=shape]m 0 0 l 8 0 8 8 0 8");
+        let mut data = vec![0u8; 8 * 8 * 4];
+        renderer.render(
+            ImageView::new(8, 8, 8 * 4, ColorType::RGBA32, vec![&mut data]).expect("ImageView must've valid dimensions!"),
+            RenderTrigger::Time(1000)
+        ).expect("Image rendering mustn't fail!");
+        assert!(data.iter().all(|sample| *sample == 0));
+    }
+}
\ No newline at end of file